@@ -0,0 +1,284 @@
+mod common;
+
+use axum_xml_up::XmlConfig;
+use common::{entries_document, extract, runtime, Entries, MapEntries};
+use criterion::measurement::{Measurement, ValueFormatter};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Allocator counting the allocations made and the bytes allocated, kept in
+/// its own benchmark so the timings of the other benchmarks aren't affected
+struct CountingAllocator;
+
+/// Number of allocations made by the [`CountingAllocator`]
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// Number of bytes currently allocated by the [`CountingAllocator`]
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+/// Highest number of bytes allocated at once by the [`CountingAllocator`]
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK.fetch_max(allocated, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Measures the number of allocations made
+struct Allocations;
+
+impl Measurement for Allocations {
+    type Intermediate = usize;
+    type Value = usize;
+
+    fn start(&self) -> Self::Intermediate {
+        ALLOCATIONS.load(Ordering::Relaxed)
+    }
+
+    fn end(&self, start: Self::Intermediate) -> Self::Value {
+        ALLOCATIONS.load(Ordering::Relaxed) - start
+    }
+
+    fn add(&self, v1: &Self::Value, v2: &Self::Value) -> Self::Value {
+        v1 + v2
+    }
+
+    fn zero(&self) -> Self::Value {
+        0
+    }
+
+    fn to_f64(&self, value: &Self::Value) -> f64 {
+        *value as f64
+    }
+
+    fn formatter(&self) -> &dyn ValueFormatter {
+        &UnitFormatter("allocs")
+    }
+}
+
+/// Measures the highest number of bytes allocated at once above the bytes
+/// allocated when the measurement started
+#[cfg(feature = "stream")]
+struct PeakMemory;
+
+#[cfg(feature = "stream")]
+impl Measurement for PeakMemory {
+    type Intermediate = usize;
+    type Value = usize;
+
+    fn start(&self) -> Self::Intermediate {
+        let allocated = ALLOCATED.load(Ordering::Relaxed);
+        PEAK.store(allocated, Ordering::Relaxed);
+        allocated
+    }
+
+    fn end(&self, start: Self::Intermediate) -> Self::Value {
+        PEAK.load(Ordering::Relaxed) - start
+    }
+
+    fn add(&self, v1: &Self::Value, v2: &Self::Value) -> Self::Value {
+        v1 + v2
+    }
+
+    fn zero(&self) -> Self::Value {
+        0
+    }
+
+    fn to_f64(&self, value: &Self::Value) -> f64 {
+        *value as f64
+    }
+
+    fn formatter(&self) -> &dyn ValueFormatter {
+        &UnitFormatter("B")
+    }
+}
+
+/// Formatter writing measured values without scaling them
+struct UnitFormatter(&'static str);
+
+impl ValueFormatter for UnitFormatter {
+    fn scale_values(&self, _typical_value: f64, _values: &mut [f64]) -> &'static str {
+        self.0
+    }
+
+    fn scale_throughputs(
+        &self,
+        _typical_value: f64,
+        _throughput: &Throughput,
+        _values: &mut [f64],
+    ) -> &'static str {
+        self.0
+    }
+
+    fn scale_for_machines(&self, _values: &mut [f64]) -> &'static str {
+        self.0
+    }
+}
+
+/// Counts the allocations of deserializing repeated names into struct fields,
+/// which are matched against the names in the document without allocating,
+/// and into map keys which allocate a `String` for every name
+fn names(c: &mut Criterion<Allocations>) {
+    let runtime = runtime();
+    let document = entries_document(100);
+    let config = XmlConfig::new();
+
+    let mut group = c.benchmark_group("names");
+    group.bench_function("struct", |b| {
+        b.iter(|| extract::<Entries>(&runtime, &document, &config))
+    });
+    group.bench_function("map", |b| {
+        b.iter(|| extract::<MapEntries>(&runtime, &document, &config))
+    });
+    group.finish();
+}
+
+/// Body generating a document with items until it reaches a size, so large
+/// documents can be sent without holding them in memory
+#[cfg(feature = "stream")]
+struct GeneratedBody {
+    /// Number of item bytes left to generate
+    remaining: usize,
+    /// Chunk of items sent repeatedly
+    chunk: bytes::Bytes,
+    /// Whether the start of the root element has been sent
+    started: bool,
+    /// Whether the end of the root element has been sent
+    finished: bool,
+}
+
+#[cfg(feature = "stream")]
+impl http_body::Body for GeneratedBody {
+    type Data = bytes::Bytes;
+    type Error = std::convert::Infallible;
+
+    fn poll_frame(
+        mut self: std::pin::Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Result<http_body::Frame<Self::Data>, Self::Error>>> {
+        let data = if !self.started {
+            self.started = true;
+            bytes::Bytes::from_static(b"<list>")
+        } else if self.remaining > 0 {
+            self.remaining = self.remaining.saturating_sub(self.chunk.len());
+            self.chunk.clone()
+        } else if !self.finished {
+            self.finished = true;
+            bytes::Bytes::from_static(b"</list>")
+        } else {
+            return std::task::Poll::Ready(None);
+        };
+
+        std::task::Poll::Ready(Some(Ok(http_body::Frame::data(data))))
+    }
+}
+
+/// Compares the peak memory usage of extracting a 100 MiB document with the
+/// buffered [`Xml`](axum_xml_up::Xml) extractor and the
+/// [`StreamingXml`](axum_xml_up::StreamingXml) extractor
+#[cfg(feature = "stream")]
+fn stream(c: &mut Criterion<PeakMemory>) {
+    use axum::body::Body;
+    use axum::extract::{DefaultBodyLimit, Request};
+    use axum::handler::Handler;
+    use axum::http::header;
+    use axum_xml_up::{StreamingXml, Xml};
+    use criterion::{Bencher, SamplingMode};
+    use serde::de::IgnoredAny;
+    use tokio::runtime::Runtime;
+
+    const SIZE: usize = 100 * 1024 * 1024;
+
+    /// Creates a request with a generated document of `SIZE` bytes
+    fn generated_request() -> Request {
+        let body = GeneratedBody {
+            remaining: SIZE,
+            chunk: bytes::Bytes::from("<item>1</item>".repeat(4096)),
+            started: false,
+            finished: false,
+        };
+        let mut request = Request::builder()
+            .header(header::CONTENT_TYPE, "application/xml")
+            .body(Body::new(body))
+            .unwrap();
+        request
+            .extensions_mut()
+            .insert(XmlConfig::new().max_body_size(Some(SIZE * 2)));
+        request
+    }
+
+    /// Measures the peak memory of each of the `iters` runs of `f` separately,
+    /// as the peak of a batch of runs is the peak of a single run
+    fn peak_per_run(iters: u64, mut f: impl FnMut()) -> usize {
+        (0..iters)
+            .map(|_| {
+                let start = PeakMemory.start();
+                f();
+                PeakMemory.end(start)
+            })
+            .sum()
+    }
+
+    /// Measures the peak memory of sending the generated document to `handler`
+    fn bench_handler<H, T>(b: &mut Bencher<'_, PeakMemory>, runtime: &Runtime, handler: H)
+    where
+        H: Handler<T, ()>,
+        T: 'static,
+    {
+        // Axum limits bodies to 2 MiB unless the default limit is disabled
+        let handler = handler.layer(DefaultBodyLimit::disable());
+        b.iter_custom(|iters| {
+            peak_per_run(iters, || {
+                let response = runtime.block_on(handler.clone().call(generated_request(), ()));
+                assert!(response.status().is_success(), "Failed to extract document");
+            })
+        })
+    }
+
+    let runtime = runtime();
+    let mut group = c.benchmark_group("stream");
+    group.sample_size(10).sampling_mode(SamplingMode::Flat);
+    group.bench_function("buffered", |b| {
+        bench_handler(b, &runtime, |Xml(_): Xml<IgnoredAny>| async {})
+    });
+    group.bench_function("streaming", |b| {
+        bench_handler(
+            b,
+            &runtime,
+            |StreamingXml(_): StreamingXml<IgnoredAny>| async {},
+        )
+    });
+    group.finish();
+}
+
+// Allocation counts don't vary between runs, which the plots can't show
+criterion_group! {
+    name = allocations;
+    config = Criterion::default().with_measurement(Allocations).without_plots();
+    targets = names
+}
+
+#[cfg(feature = "stream")]
+criterion_group! {
+    name = peak_memory;
+    config = Criterion::default().with_measurement(PeakMemory).without_plots();
+    targets = stream
+}
+
+#[cfg(not(feature = "stream"))]
+criterion_main!(allocations);
+#[cfg(feature = "stream")]
+criterion_main!(allocations, peak_memory);
@@ -0,0 +1,69 @@
+use axum::body::Body;
+use axum::extract::{FromRequest, Request};
+use axum::http::header;
+use axum_xml_up::{Xml, XmlConfig};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::HashMap;
+use tokio::runtime::Runtime;
+
+/// Document containing entries with repeated element names
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+pub struct Entries {
+    entry: Vec<Entry>,
+}
+
+/// Entry with its names matched against the struct fields
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+pub struct Entry {
+    name: String,
+    value: String,
+}
+
+/// Document containing entries with their names stored as map keys
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+pub struct MapEntries {
+    entry: Vec<HashMap<String, String>>,
+}
+
+/// Creates a document with `count` entries
+pub fn entries_document(count: usize) -> String {
+    let mut document = String::from("<entries>");
+    for i in 0..count {
+        document.push_str(&format!(
+            "<entry><name>{i}</name><value>{i}</value></entry>"
+        ));
+    }
+    document.push_str("</entries>");
+    document
+}
+
+/// Creates a request for the provided `document` using `config`
+pub fn request(document: &str, config: &XmlConfig) -> Request {
+    let mut request = Request::builder()
+        .header(header::CONTENT_TYPE, "application/xml")
+        .body(Body::from(document.to_string()))
+        .unwrap();
+    request.extensions_mut().insert(config.clone());
+    request
+}
+
+/// Extracts a `T` from `document` using `config`
+pub fn extract<T>(runtime: &Runtime, document: &str, config: &XmlConfig) -> T
+where
+    T: DeserializeOwned,
+{
+    let Xml(value) = runtime
+        .block_on(Xml::<T>::from_request(request(document, config), &()))
+        .expect("Failed to extract document");
+    value
+}
+
+pub fn runtime() -> Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .expect("Failed to create runtime")
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use axum_xml_up::XmlConfig;
+use common::{entries_document, extract, runtime, Entries, MapEntries};
+use criterion::{criterion_group, criterion_main, Criterion};
+use serde::Deserialize;
+
+/// Document containing a list of items
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct List {
+    item: Vec<u32>,
+    other: Vec<u32>,
+}
+
+/// Creates a document with `count` items, when `overlapped` is set the
+/// `item` and `other` elements are interleaved
+fn document(count: usize, overlapped: bool) -> String {
+    let mut document = String::from("<list>");
+    if overlapped {
+        for i in 0..count {
+            document.push_str(&format!("<item>{i}</item><other>{i}</other>"));
+        }
+    } else {
+        for i in 0..count {
+            document.push_str(&format!("<item>{i}</item>"));
+        }
+        for i in 0..count {
+            document.push_str(&format!("<other>{i}</other>"));
+        }
+    }
+    document.push_str("</list>");
+    document
+}
+
+fn deserialize(c: &mut Criterion) {
+    let runtime = runtime();
+    let document = document(100, false);
+    let config = XmlConfig::new();
+
+    c.bench_function("deserialize", |b| {
+        b.iter(|| extract::<List>(&runtime, &document, &config))
+    });
+}
+
+/// Compares deserializing repeated names into struct fields, which are matched
+/// against the names in the document without allocating, with deserializing
+/// them into map keys which allocate a `String` for every name. The `allocations`
+/// benchmark counts the allocations of the same documents.
+fn names(c: &mut Criterion) {
+    let runtime = runtime();
+    let document = entries_document(100);
+    let config = XmlConfig::new();
+
+    let mut group = c.benchmark_group("names");
+    group.bench_function("struct", |b| {
+        b.iter(|| extract::<Entries>(&runtime, &document, &config))
+    });
+    group.bench_function("map", |b| {
+        b.iter(|| extract::<MapEntries>(&runtime, &document, &config))
+    });
+    group.finish();
+}
+
+/// Compares the throughput of different event buffer sizes on a document
+/// that requires buffering
+#[cfg(feature = "overlapped-lists")]
+fn event_buffer_size(c: &mut Criterion) {
+    use std::num::NonZeroUsize;
+
+    let runtime = runtime();
+    let document = document(100, true);
+    let mut group = c.benchmark_group("event_buffer_size");
+
+    for size in [Some(512), Some(1024), Some(4096), None] {
+        let config = XmlConfig::new().event_buffer_size(size.and_then(NonZeroUsize::new));
+        let name = match size {
+            Some(size) => size.to_string(),
+            None => "unlimited".to_string(),
+        };
+
+        group.bench_function(name, |b| {
+            b.iter(|| extract::<List>(&runtime, &document, &config))
+        });
+    }
+
+    group.finish();
+}
+
+#[cfg(not(feature = "overlapped-lists"))]
+criterion_group!(benches, deserialize, names);
+#[cfg(feature = "overlapped-lists")]
+criterion_group!(benches, deserialize, names, event_buffer_size);
+criterion_main!(benches);
@@ -0,0 +1,175 @@
+use axum_core::extract::FromRequestParts;
+use core::pin::Pin;
+use http::request::Parts;
+use http::{header, HeaderMap};
+use std::convert::Infallible;
+use std::future::Future;
+
+/// Extractor for whether the client accepts an XML response, based on the
+/// `Accept` request header
+///
+/// XML is acceptable when the client didn't send an `Accept` header, or when
+/// one of the accepted media types is an XML type (such as `application/xml`,
+/// `text/xml` or `application/soap+xml`) or a wildcard covering them (`*/*`,
+/// `application/*` or `text/*`). The most specific of the media ranges
+/// covering XML decides, so XML is not acceptable when that range has a zero
+/// quality value, such as with `application/xml;q=0, */*`.
+///
+/// ```rust,no_run
+/// use axum::http::StatusCode;
+/// use axum_xml_up::{AcceptXml, Xml};
+///
+/// async fn get_report(accept: AcceptXml) -> Result<Xml<Vec<u32>>, StatusCode> {
+///     if !accept.accepted() {
+///         return Err(StatusCode::NOT_ACCEPTABLE);
+///     }
+///     Ok(Xml(vec![1, 2, 3]))
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AcceptXml {
+    /// Whether XML is acceptable
+    accepted: bool,
+}
+
+impl AcceptXml {
+    /// Determines whether XML is acceptable from the `Accept` headers
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let mut ranges = media_ranges(headers).peekable();
+        if ranges.peek().is_none() {
+            return Self { accepted: true };
+        }
+
+        // The most specific range covering XML decides whether it is acceptable
+        let mut xml = Preference::default();
+        for (index, (mime, quality)) in ranges.enumerate() {
+            if accepts_xml(&mime) {
+                xml.update(quality, specificity(&mime), index);
+            }
+        }
+
+        Self {
+            accepted: xml.is_acceptable(),
+        }
+    }
+
+    /// Whether the client accepts an XML response
+    pub fn accepted(&self) -> bool {
+        self.accepted
+    }
+}
+
+impl Default for AcceptXml {
+    fn default() -> Self {
+        Self { accepted: true }
+    }
+}
+
+impl<S> FromRequestParts<S> for AcceptXml
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    fn from_request_parts<'parts, 'state, 'future>(
+        parts: &'parts mut Parts,
+        _state: &'state S,
+    ) -> Pin<Box<dyn Future<Output = Result<Self, Self::Rejection>> + Send + 'future>>
+    where
+        'parts: 'future,
+        'state: 'future,
+        Self: 'future,
+    {
+        Box::pin(async move { Ok(Self::from_headers(&parts.headers)) })
+    }
+}
+
+/// Parses the media ranges of the `Accept` headers along with their quality
+/// values, ranges that can't be parsed are skipped
+pub(crate) fn media_ranges(headers: &HeaderMap) -> impl Iterator<Item = (mime::Mime, f32)> + '_ {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|value| value.trim().parse::<mime::Mime>().ok())
+        .map(|mime| {
+            let quality = mime
+                .get_param("q")
+                .and_then(|quality| quality.as_str().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (mime, quality)
+        })
+}
+
+/// Checks whether the media range `mime` includes an XML type
+pub(crate) fn accepts_xml(mime: &mime::Mime) -> bool {
+    let type_ = mime.type_();
+    crate::is_xml_type(mime)
+        || (type_ == mime::STAR && mime.subtype() == mime::STAR)
+        || ((type_ == mime::APPLICATION || type_ == mime::TEXT) && mime.subtype() == mime::STAR)
+}
+
+/// How acceptable a format is, from the most specific media range covering it
+#[derive(Debug, Default)]
+pub(crate) struct Preference {
+    /// Quality value of the media range, `None` when no range covers the format
+    quality: Option<f32>,
+    /// Specificity of the media range, `0` for `*/*`, `1` for `type/*` and `2`
+    /// for a full media type
+    specificity: u8,
+    /// Position of the media range in the `Accept` headers, only compared by
+    /// [`Preference::is_preferred_to`]
+    #[cfg_attr(not(feature = "json"), allow(dead_code))]
+    index: usize,
+}
+
+impl Preference {
+    /// Records a media range covering the format, ranges that are less specific
+    /// than an earlier one are ignored
+    pub(crate) fn update(&mut self, quality: f32, specificity: u8, index: usize) {
+        let replace = match self.quality {
+            None => true,
+            Some(current) => {
+                specificity > self.specificity
+                    || (specificity == self.specificity && quality > current)
+            }
+        };
+
+        if replace {
+            *self = Self {
+                quality: Some(quality),
+                specificity,
+                index,
+            };
+        }
+    }
+
+    /// Whether a media range covers the format with a non-zero quality value
+    pub(crate) fn is_acceptable(&self) -> bool {
+        self.quality.is_some_and(|quality| quality > 0.0)
+    }
+
+    /// Whether the format is more acceptable than the `other` format
+    #[cfg(feature = "json")]
+    pub(crate) fn is_preferred_to(&self, other: &Self) -> bool {
+        let Some(quality) = self.quality.filter(|quality| *quality > 0.0) else {
+            return false;
+        };
+        let other_quality = other.quality.unwrap_or(0.0);
+
+        quality > other_quality
+            || (quality == other_quality
+                && (self.specificity > other.specificity
+                    || (self.specificity == other.specificity && self.index < other.index)))
+    }
+}
+
+/// Specificity of the media range `mime`, see [`Preference::specificity`]
+pub(crate) fn specificity(mime: &mime::Mime) -> u8 {
+    match (mime.type_() == mime::STAR, mime.subtype() == mime::STAR) {
+        (true, _) => 0,
+        (false, true) => 1,
+        (false, false) => 2,
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{Xml, XmlResponse};
+use axum_core::body::Body;
+use axum_core::extract::Request;
+use bytes::Bytes;
+use http::{header, HeaderValue, Method, Uri};
+use quick_xml::DeError;
+use serde::Serialize;
+
+impl<T> Xml<T>
+where
+    T: Serialize,
+{
+    /// Serializes the value the same way as a response, for use as the body of
+    /// a request sent to an endpoint using the [`Xml`] extractor
+    ///
+    /// Requires the `client` feature.
+    pub fn to_bytes(&self) -> Result<Bytes, DeError> {
+        XmlResponse::new(&self.0).serialize()
+    }
+
+    /// Creates a `POST` request to `uri` with the serialized value as its body
+    /// and `Content-Type: application/xml`, allowing round-trip tests to send
+    /// a value through the extractor
+    ///
+    /// ```
+    /// use axum::extract::FromRequest;
+    /// use axum_xml_up::Xml;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    /// struct User {
+    ///     name: String,
+    /// }
+    ///
+    /// # async {
+    /// let user = User { name: "Jacob".to_string() };
+    /// let request = Xml(user).into_request("/users".parse().unwrap()).unwrap();
+    /// let Xml(user) = Xml::<User>::from_request(request, &()).await.unwrap();
+    /// assert_eq!(user.name, "Jacob");
+    /// # };
+    /// ```
+    ///
+    /// Requires the `client` feature.
+    pub fn into_request(self, uri: Uri) -> Result<Request, DeError> {
+        let mut request = Request::new(Body::from(self.to_bytes()?));
+        *request.method_mut() = Method::POST;
+        *request.uri_mut() = uri;
+        request.headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/xml"),
+        );
+        Ok(request)
+    }
+}
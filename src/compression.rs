@@ -0,0 +1,99 @@
+use axum_core::extract::FromRequestParts;
+use core::pin::Pin;
+use http::request::Parts;
+use http::{header, HeaderMap};
+use std::convert::Infallible;
+use std::future::Future;
+
+/// Extractor for the encodings the client accepts in the response, based on the
+/// `Accept-Encoding` request header
+///
+/// Used with [`XmlResponse::compressed_if_accepted`] to only compress responses
+/// for clients that support it.
+///
+/// ```rust,no_run
+/// use axum_xml_up::{AcceptEncoding, Xml, XmlResponse};
+///
+/// async fn get_report(accept: AcceptEncoding) -> XmlResponse<Vec<u32>> {
+///     Xml(vec![1, 2, 3]).compressed_if_accepted(accept)
+/// }
+/// ```
+///
+/// [`XmlResponse::compressed_if_accepted`]: crate::XmlResponse::compressed_if_accepted
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AcceptEncoding {
+    /// Whether gzip is accepted
+    gzip: bool,
+}
+
+impl AcceptEncoding {
+    /// Determines the accepted encodings from the `Accept-Encoding` headers
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        // Whether gzip is accepted when listed by name and by the `*` wildcard
+        let mut named: Option<bool> = None;
+        let mut wildcard = false;
+
+        let codings = headers
+            .get_all(header::ACCEPT_ENCODING)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','));
+
+        for coding in codings {
+            let mut parts = coding.split(';').map(str::trim);
+            let name = parts.next().unwrap_or_default();
+
+            // Codings with a zero quality value are not acceptable
+            let accepted = !parts
+                .filter_map(|param| param.strip_prefix("q="))
+                .any(|quality| quality.parse::<f32>().is_ok_and(|quality| quality <= 0.0));
+
+            if name.eq_ignore_ascii_case("gzip") || name.eq_ignore_ascii_case("x-gzip") {
+                named = Some(named.unwrap_or(false) || accepted);
+            } else if name == "*" {
+                wildcard |= accepted;
+            }
+        }
+
+        // The wildcard only applies to codings that aren't listed by name
+        Self {
+            gzip: named.unwrap_or(wildcard),
+        }
+    }
+
+    /// Whether the client accepts gzip encoded responses
+    pub fn gzip(&self) -> bool {
+        self.gzip
+    }
+}
+
+impl<S> FromRequestParts<S> for AcceptEncoding
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    fn from_request_parts<'parts, 'state, 'future>(
+        parts: &'parts mut Parts,
+        _state: &'state S,
+    ) -> Pin<Box<dyn Future<Output = Result<Self, Self::Rejection>> + Send + 'future>>
+    where
+        'parts: 'future,
+        'state: 'future,
+        Self: 'future,
+    {
+        Box::pin(async move { Ok(Self::from_headers(&parts.headers)) })
+    }
+}
+
+/// Compresses the provided `bytes` using gzip
+pub(crate) fn compress(bytes: &[u8]) -> Vec<u8> {
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(bytes)
+        .and_then(|_| encoder.finish())
+        .expect("Compressing into a Vec should never fail")
+}
@@ -0,0 +1,750 @@
+use crate::rejection::XmlRejection;
+use axum_core::extract::Request;
+use http::{header, HeaderName, HeaderValue};
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+#[cfg(feature = "overlapped-lists")]
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+
+/// Default maximum size of the request body in bytes
+const DEFAULT_MAX_BODY_SIZE: usize = 2 * 1024 * 1024;
+
+/// Default maximum nesting depth of elements
+const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// Configuration for the [`Xml`](crate::Xml) extractor
+///
+/// The extractor looks up the config to use in the following order:
+///
+/// 1. A config in the request extensions, provided for a router (or a single
+///    route) using an `Extension` layer. Allows middleware to override the config
+///    for specific requests.
+/// 2. The router state when the state is an `XmlConfig`.
+/// 3. The default config only accepting the listed content types when the
+///    router state is a `Vec<mime::Mime>`, see
+///    [`XmlConfig::accepted_content_types`].
+/// 4. [`XmlConfig::default`]
+///
+/// ```rust,no_run
+/// use axum::{routing::post, Extension, Router};
+/// use axum_xml_up::{Xml, XmlConfig};
+///
+/// # async fn handler(Xml(_): Xml<()>) {}
+/// let config = XmlConfig::new().entity("company", "Acme Inc.");
+///
+/// let app: Router = Router::new()
+///     .route("/", post(handler))
+///     .layer(Extension(config));
+/// ```
+#[derive(Debug, Clone)]
+pub struct XmlConfig {
+    /// Custom named entities that can be referenced from text content
+    pub(crate) entities: HashMap<String, String>,
+    /// Name of the header containing the content type of the body
+    pub(crate) content_type_header: HeaderName,
+    /// Whether the `application/*` content type is accepted
+    pub(crate) accept_wildcard_type: bool,
+    /// Matcher used instead of the default XML content type check
+    pub(crate) content_type_matcher: Option<ContentTypeMatcher>,
+    /// Whether requests without an XML content type are rejected
+    pub(crate) require_content_type: bool,
+    /// Names of the root elements documents are allowed to have
+    pub(crate) allowed_roots: Option<Vec<String>>,
+    /// Whether documents without a root element are rejected
+    pub(crate) require_root_element: bool,
+    /// Whether bodies with only a byte order mark or whitespace are empty
+    pub(crate) blank_body_as_empty: bool,
+    /// Whether documents without a root element are read as an empty element
+    pub(crate) missing_root_as_default: bool,
+    /// Paths of the elements and attributes documents must contain
+    pub(crate) required_paths: Option<Vec<String>>,
+    /// Whether body read failures use the crate error format
+    pub(crate) normalize_body_errors: bool,
+    /// Maximum size of the request body in bytes
+    pub(crate) max_body_size: Option<usize>,
+    /// Whether the body is wrapped in a synthetic root element
+    pub(crate) wrap_fragments: bool,
+    /// Whether whitespace is trimmed from attribute values
+    pub(crate) trim_attribute_values: bool,
+    /// Whether attributes can be deserialized into fields without the `@` prefix
+    pub(crate) unprefixed_attributes: bool,
+    /// Whether `\r\n` and `\r` line endings are normalized to `\n`
+    pub(crate) normalize_line_endings: bool,
+    /// Whether common mistakes in documents are repaired before parsing
+    pub(crate) repair_malformed: bool,
+    /// Whether documents declaring a version other than `1.0` are rejected
+    pub(crate) require_version_1_0: bool,
+    /// Whether documents containing processing instructions are rejected
+    pub(crate) reject_processing_instructions: bool,
+    /// Whether documents containing a DOCTYPE declaration are rejected
+    pub(crate) forbid_doctype: bool,
+    /// Whether bodies starting with a byte order mark are rejected
+    pub(crate) reject_byte_order_mark: bool,
+    /// Maximum nesting depth of elements in the document
+    pub(crate) max_depth: Option<usize>,
+    /// Maximum length of a comment in bytes
+    pub(crate) max_comment_len: Option<usize>,
+    /// Maximum number of comments in the document
+    pub(crate) max_comments: Option<usize>,
+    /// Maximum number of distinct element names in a document
+    pub(crate) max_unique_element_names: Option<usize>,
+    /// Maximum number of custom entity references in a document
+    pub(crate) max_entity_expansions: Option<usize>,
+    /// Maximum ratio of elements to content in the document
+    pub(crate) max_markup_ratio: Option<MarkupRatio>,
+    /// Whether syntax errors include a hexdump of the body around the error
+    #[cfg(feature = "hexdump")]
+    pub(crate) hexdump_errors: bool,
+    /// Callback invoked for each rejection
+    pub(crate) on_rejection: Option<RejectionCallback>,
+    /// Digest of the raw body checked against a request header
+    pub(crate) body_digest: Option<BodyDigest>,
+    /// Maximum number of events buffered while looking for overlapped list items
+    #[cfg(feature = "overlapped-lists")]
+    pub(crate) event_buffer_size: Option<NonZeroUsize>,
+}
+
+impl Default for XmlConfig {
+    fn default() -> Self {
+        Self {
+            entities: HashMap::new(),
+            content_type_header: header::CONTENT_TYPE,
+            accept_wildcard_type: false,
+            content_type_matcher: None,
+            require_content_type: true,
+            allowed_roots: None,
+            require_root_element: true,
+            blank_body_as_empty: false,
+            missing_root_as_default: false,
+            required_paths: None,
+            normalize_body_errors: false,
+            max_body_size: Some(DEFAULT_MAX_BODY_SIZE),
+            wrap_fragments: false,
+            trim_attribute_values: false,
+            unprefixed_attributes: false,
+            normalize_line_endings: true,
+            repair_malformed: false,
+            require_version_1_0: false,
+            reject_processing_instructions: false,
+            forbid_doctype: false,
+            reject_byte_order_mark: false,
+            max_depth: Some(DEFAULT_MAX_DEPTH),
+            max_comment_len: None,
+            max_comments: None,
+            max_unique_element_names: None,
+            max_entity_expansions: None,
+            max_markup_ratio: None,
+            #[cfg(feature = "hexdump")]
+            hexdump_errors: false,
+            on_rejection: None,
+            body_digest: None,
+            #[cfg(feature = "overlapped-lists")]
+            event_buffer_size: NonZeroUsize::new(1024),
+        }
+    }
+}
+
+impl XmlConfig {
+    /// Creates a new config with the default settings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolves the config to use for `req`, preferring the config in the request
+    /// extensions over the config in the router `state`
+    pub(crate) fn resolve<S>(req: &mut Request, state: &S) -> Self
+    where
+        S: 'static,
+    {
+        if let Some(config) = req.extensions_mut().remove::<XmlConfig>() {
+            return config;
+        }
+
+        if let Some(config) = (state as &dyn Any).downcast_ref::<XmlConfig>() {
+            return config.clone();
+        }
+
+        if let Some(types) = (state as &dyn Any).downcast_ref::<Vec<mime::Mime>>() {
+            return Self::default()
+                .accepted_content_types(types.iter().map(|mime| mime.essence_str().to_string()));
+        }
+
+        Self::default()
+    }
+
+    /// Invokes the rejection callback when `result` is a rejection
+    pub(crate) fn report<T>(&self, result: Result<T, XmlRejection>) -> Result<T, XmlRejection> {
+        if let (Err(err), Some(callback)) = (&result, &self.on_rejection) {
+            (callback.0)(err);
+        }
+        result
+    }
+
+    /// Registers a custom named entity, allowing `&name;` to be used in the text
+    /// content of a document to refer to `value`.
+    ///
+    /// Only entities registered here are expanded, entities declared by the
+    /// document itself (internal or external DTD) are never resolved. The value
+    /// is inserted as plain text so entities cannot expand recursively.
+    pub fn entity(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.entities.insert(name.into(), value.into());
+        self
+    }
+
+    /// Sets the header the content type of the body is read from, for requests
+    /// forwarded by gateways that move the original content type to another
+    /// header such as `X-Original-Content-Type`. Defaults to `Content-Type`.
+    pub fn content_type_header(mut self, name: HeaderName) -> Self {
+        self.content_type_header = name;
+        self
+    }
+
+    /// Sets whether requests with the wildcard `Content-Type: application/*`
+    /// are accepted as XML, for clients that don't send a specific type.
+    /// Disabled by default.
+    pub fn accept_wildcard_type(mut self, accept: bool) -> Self {
+        self.accept_wildcard_type = accept;
+        self
+    }
+
+    /// Sets whether requests without an accepted XML content type are rejected
+    /// with [`XmlRejection::MissingXMLContentType`]. When disabled the body is
+    /// parsed as XML regardless of its content type, for clients such as curl
+    /// one-liners and embedded devices that don't set the header, and is only
+    /// rejected when parsing fails. Enabled by default.
+    pub fn require_content_type(mut self, require: bool) -> Self {
+        self.require_content_type = require;
+        self
+    }
+
+    /// Accepts bodies whose content type matches the `predicate` instead of
+    /// the default check for an `application` or `text` type with an `xml`
+    /// subtype or suffix. Other bodies are rejected with
+    /// [`XmlRejection::MissingXMLContentType`].
+    ///
+    /// ```
+    /// use axum_xml_up::XmlConfig;
+    ///
+    /// let config = XmlConfig::new()
+    ///     .accept_content_type(|mime| mime.subtype() == "xml" || mime.subtype() == "octet-stream");
+    /// ```
+    pub fn accept_content_type<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&mime::Mime) -> bool + Send + Sync + 'static,
+    {
+        self.content_type_matcher = Some(ContentTypeMatcher(Arc::new(predicate)));
+        self
+    }
+
+    /// Only accepts bodies with one of the provided content `types`, compared
+    /// without their parameters, instead of the default XML content types.
+    /// See [`XmlConfig::accept_content_type`].
+    ///
+    /// ```
+    /// use axum_xml_up::XmlConfig;
+    ///
+    /// let config = XmlConfig::new()
+    ///     .accepted_content_types(["application/vnd.myapp+xml", "application/octet-stream"]);
+    /// ```
+    pub fn accepted_content_types<I>(self, types: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let types: Vec<String> = types.into_iter().map(Into::into).collect();
+        self.accept_content_type(move |mime| {
+            types
+                .iter()
+                .any(|value| value.eq_ignore_ascii_case(mime.essence_str()))
+        })
+    }
+
+    /// Only accepts documents whose root element has one of the provided
+    /// `names`, other documents are rejected with [`XmlRejection::RootNotAllowed`]
+    /// before they are deserialized. All root elements are allowed by default.
+    ///
+    /// ```
+    /// use axum_xml_up::XmlConfig;
+    ///
+    /// let config = XmlConfig::new().allowed_roots(["Order", "Refund"]);
+    /// ```
+    ///
+    /// [`XmlRejection::RootNotAllowed`]: crate::XmlRejection::RootNotAllowed
+    pub fn allowed_roots<I>(mut self, names: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.allowed_roots = Some(names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Sets whether documents without a root element (e.g. only a prolog or
+    /// whitespace) are rejected with [`XmlRejection::NoRootElement`] before
+    /// deserialization is attempted. Bodies containing text without any
+    /// elements, such as `just text`, are rejected with
+    /// [`XmlRejection::NotADocument`]. Enabled by default.
+    ///
+    /// [`XmlRejection::NoRootElement`]: crate::XmlRejection::NoRootElement
+    /// [`XmlRejection::NotADocument`]: crate::XmlRejection::NotADocument
+    pub fn require_root_element(mut self, require: bool) -> Self {
+        self.require_root_element = require;
+        self
+    }
+
+    /// Sets whether bodies containing only a byte order mark and whitespace
+    /// are treated the same as an empty body, for clients that send them as
+    /// a "nothing" body. Such bodies are then extracted as `None` by
+    /// [`OptionalXml`] and rejected with [`XmlRejection::NoRootElement`] by
+    /// other extractors. Disabled by default.
+    ///
+    /// [`OptionalXml`]: crate::OptionalXml
+    /// [`XmlRejection::NoRootElement`]: crate::XmlRejection::NoRootElement
+    pub fn blank_body_as_empty(mut self, empty: bool) -> Self {
+        self.blank_body_as_empty = empty;
+        self
+    }
+
+    /// Sets whether documents without a root element, such as an empty body,
+    /// are deserialized as an empty root element instead of being rejected
+    /// with [`XmlRejection::NoRootElement`]. Types using `#[serde(default)]`
+    /// at the container level are then filled with their defaults, while
+    /// other types are rejected for their missing fields. The content type
+    /// is still checked. Disabled by default.
+    ///
+    /// ```
+    /// use axum_xml_up::XmlConfig;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize, Default)]
+    /// #[serde(default)]
+    /// struct Settings {
+    ///     theme: String,
+    ///     page_size: u32,
+    /// }
+    ///
+    /// // Both `<Settings/>` and an empty body produce `Settings::default()`
+    /// let config = XmlConfig::new().missing_root_as_default(true);
+    /// ```
+    ///
+    /// [`XmlRejection::NoRootElement`]: crate::XmlRejection::NoRootElement
+    pub fn missing_root_as_default(mut self, default: bool) -> Self {
+        self.missing_root_as_default = default;
+        self
+    }
+
+    /// Rejects documents missing any of the element or attribute `paths` with
+    /// [`XmlRejection::MissingRequiredPath`] naming the first missing path,
+    /// before the document is deserialized. Paths are the `/` separated names
+    /// of the elements starting from the root element, with an optional final
+    /// `@` prefixed attribute name such as `Order/customer/@id`. Names are
+    /// compared including their namespace prefix.
+    ///
+    /// The required paths of a type can be kept next to it, giving targeted
+    /// errors for the fields serde would report as missing:
+    ///
+    /// ```
+    /// use axum_xml_up::XmlConfig;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Order {
+    ///     #[serde(rename = "@id")]
+    ///     id: u32,
+    ///     customer: Customer,
+    /// }
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Customer {
+    ///     email: String,
+    /// }
+    ///
+    /// impl Order {
+    ///     const REQUIRED: [&'static str; 2] = ["Order/@id", "Order/customer/email"];
+    /// }
+    ///
+    /// let config = XmlConfig::new().required_paths(Order::REQUIRED);
+    /// ```
+    ///
+    /// [`XmlRejection::MissingRequiredPath`]: crate::XmlRejection::MissingRequiredPath
+    pub fn required_paths<I>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.required_paths = Some(paths.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Sets whether failures to read the request body (such as exceeding the
+    /// `DefaultBodyLimit`) are reported as [`XmlRejection::BodyReadFailed`]
+    /// instead of the response from axum's own rejection. The status code of the
+    /// underlying rejection is kept. Disabled by default.
+    ///
+    /// [`XmlRejection::BodyReadFailed`]: crate::XmlRejection::BodyReadFailed
+    pub fn normalize_body_errors(mut self, normalize: bool) -> Self {
+        self.normalize_body_errors = normalize;
+        self
+    }
+
+    /// Rejects request bodies larger than `limit` bytes with
+    /// [`XmlRejection::BodyTooLarge`], reporting the limit and the size of the
+    /// body when it is known from the `Content-Length` header. Bodies with a
+    /// `Content-Length` over the limit are rejected without being read, other
+    /// bodies are rejected once the limit is reached while reading them.
+    ///
+    /// Defaults to 2 MiB. The limit applies in addition to axum's
+    /// `DefaultBodyLimit`, which must also be raised for limits above its
+    /// default of 2 MiB. `None` removes the limit, leaving only the
+    /// `DefaultBodyLimit`.
+    ///
+    /// [`XmlRejection::BodyTooLarge`]: crate::XmlRejection::BodyTooLarge
+    pub fn max_body_size(mut self, limit: Option<usize>) -> Self {
+        self.max_body_size = limit;
+        self
+    }
+
+    /// Sets whether the body is wrapped in a synthetic root element before it is
+    /// deserialized, allowing fragments with multiple top level elements such as
+    /// `<name>..</name><email>..</email>` to be deserialized into a struct whose
+    /// fields are the top level elements. Disabled by default.
+    pub fn wrap_fragments(mut self, wrap: bool) -> Self {
+        self.wrap_fragments = wrap;
+        self
+    }
+
+    /// Sets whether leading and trailing whitespace is trimmed from attribute
+    /// values before deserialization, so `foo=" bar "` deserializes as `"bar"`.
+    /// Disabled by default.
+    pub fn trim_attribute_values(mut self, trim: bool) -> Self {
+        self.trim_attribute_values = trim;
+        self
+    }
+
+    /// Sets whether attributes can also be deserialized into fields without the
+    /// `@` prefix, the convention used by quick-xml before `0.27`. Disabled by
+    /// default.
+    ///
+    /// When enabled each attribute other than namespace declarations is also
+    /// provided as a child element of the same name, so `<user id="1"/>`
+    /// deserializes into both a `@id` field and an `id` field. This allows
+    /// types using either convention to accept the same documents while they
+    /// are migrated. Types that use `$value`, reject unknown fields, or have
+    /// both an attribute and a child element with the same name should not be
+    /// used with this option.
+    pub fn unprefixed_attributes(mut self, unprefixed: bool) -> Self {
+        self.unprefixed_attributes = unprefixed;
+        self
+    }
+
+    /// Sets whether `\r\n` and lone `\r` line endings are normalized to `\n`
+    /// before deserialization, as required by the XML specification, so text
+    /// and attribute values read the same regardless of the platform of the
+    /// producer. Carriage returns written as the `&#13;` character reference
+    /// are kept. Enabled by default.
+    pub fn normalize_line_endings(mut self, normalize: bool) -> Self {
+        self.normalize_line_endings = normalize;
+        self
+    }
+
+    /// Sets whether common mistakes made by buggy producers are repaired before
+    /// the body is parsed. Disabled by default.
+    ///
+    /// Repairing fixes the following, outside of comments, CDATA sections and
+    /// processing instructions:
+    ///
+    /// * Ampersands that don't start a character or entity reference, such as
+    ///   `Tom & Jerry`, are escaped as `&amp;`
+    /// * Start tags of the HTML void elements (`br`, `hr`, `img`, `input`,
+    ///   `meta` and similar) are treated as empty elements, so `<br>` is read
+    ///   as `<br/>` and any `</br>` end tag is removed
+    ///
+    /// Repairing changes the meaning of documents that are otherwise malformed,
+    /// such as a document that intentionally uses a `<link>` element with
+    /// content, so it should only be enabled for producers known to make
+    /// these mistakes.
+    pub fn repair_malformed(mut self, repair: bool) -> Self {
+        self.repair_malformed = repair;
+        self
+    }
+
+    /// Sets whether documents with an XML declaration for a version other than
+    /// `1.0`, such as `<?xml version="1.1"?>`, are rejected with
+    /// [`XmlRejection::UnsupportedVersion`]. Documents without a declaration
+    /// are always accepted. Disabled by default, accepting all versions.
+    ///
+    /// [`XmlRejection::UnsupportedVersion`]: crate::XmlRejection::UnsupportedVersion
+    pub fn require_version_1_0(mut self, require: bool) -> Self {
+        self.require_version_1_0 = require;
+        self
+    }
+
+    /// Sets whether documents containing processing instructions other than the
+    /// XML declaration, such as `<?xml-stylesheet ...?>`, are rejected with
+    /// [`XmlRejection::ProcessingInstruction`]. Disabled by default, processing
+    /// instructions are skipped when deserializing.
+    ///
+    /// [`XmlRejection::ProcessingInstruction`]: crate::XmlRejection::ProcessingInstruction
+    pub fn reject_processing_instructions(mut self, reject: bool) -> Self {
+        self.reject_processing_instructions = reject;
+        self
+    }
+
+    /// Sets whether documents containing a `<!DOCTYPE ...>` declaration are
+    /// rejected with [`XmlRejection::ForbiddenDoctype`], refusing the internal
+    /// and external entity declarations used by XXE attacks outright.
+    ///
+    /// Disabled by default as the entities declared by a document are never
+    /// expanded or fetched, only the entities registered with
+    /// [`XmlConfig::entity`] are. Enable it for deployments that must refuse
+    /// DTDs entirely.
+    ///
+    /// [`XmlRejection::ForbiddenDoctype`]: crate::XmlRejection::ForbiddenDoctype
+    pub fn forbid_doctype(mut self, forbid: bool) -> Self {
+        self.forbid_doctype = forbid;
+        self
+    }
+
+    /// Sets whether bodies starting with a UTF-8 byte order mark are rejected
+    /// with [`XmlRejection::ByteOrderMark`], for strict contracts that forbid
+    /// it. Disabled by default, the byte order mark is stripped before the
+    /// body is parsed.
+    ///
+    /// [`XmlRejection::ByteOrderMark`]: crate::XmlRejection::ByteOrderMark
+    pub fn reject_byte_order_mark(mut self, reject: bool) -> Self {
+        self.reject_byte_order_mark = reject;
+        self
+    }
+
+    /// Sets the maximum nesting depth of elements, documents nested deeper are
+    /// rejected with [`XmlLimit::Depth`] before deserialization so recursive
+    /// types such as trees can't overflow the stack. `None` removes the limit.
+    ///
+    /// Defaults to `128`, the root element is at a depth of `1`.
+    ///
+    /// [`XmlLimit::Depth`]: crate::XmlLimit::Depth
+    pub fn max_depth(mut self, depth: Option<usize>) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Rejects documents containing a comment longer than `len` bytes with
+    /// [`XmlLimit::CommentLength`]. Disabled by default.
+    ///
+    /// [`XmlLimit::CommentLength`]: crate::XmlLimit::CommentLength
+    pub fn max_comment_len(mut self, len: usize) -> Self {
+        self.max_comment_len = Some(len);
+        self
+    }
+
+    /// Rejects documents containing more than `count` comments with
+    /// [`XmlLimit::Comments`]. Disabled by default.
+    ///
+    /// [`XmlLimit::Comments`]: crate::XmlLimit::Comments
+    pub fn max_comments(mut self, count: usize) -> Self {
+        self.max_comments = Some(count);
+        self
+    }
+
+    /// Rejects documents using more than `count` distinct element names with
+    /// [`XmlLimit::UniqueNames`], guarding maps keyed by element name against
+    /// documents that introduce thousands of names. Disabled by default.
+    ///
+    /// [`XmlLimit::UniqueNames`]: crate::XmlLimit::UniqueNames
+    pub fn max_unique_element_names(mut self, count: usize) -> Self {
+        self.max_unique_element_names = Some(count);
+        self
+    }
+
+    /// Rejects documents referencing entities more than `count` times in text
+    /// content and attribute values with [`XmlLimit::EntityExpansions`],
+    /// bounding the memory used by expanding entities registered using
+    /// [`XmlConfig::entity`]. References to the predefined entities such as
+    /// `&amp;` and character references don't count. Disabled by default.
+    ///
+    /// Entities declared by the document itself are never expanded, so
+    /// documents relying on nested declarations to expand exponentially
+    /// (the "billion laughs" attack) are rejected as invalid regardless of
+    /// this limit.
+    ///
+    /// [`XmlLimit::EntityExpansions`]: crate::XmlLimit::EntityExpansions
+    pub fn max_entity_expansions(mut self, count: usize) -> Self {
+        self.max_entity_expansions = Some(count);
+        self
+    }
+
+    /// Rejects documents with more than `ratio` elements for each byte of text
+    /// and attribute value content with [`XmlLimit::MarkupRatio`], guarding
+    /// against markup heavy documents designed to be expensive to parse. Only
+    /// applies to documents with more than `min_elements` elements so small
+    /// documents without content are still accepted. Disabled by default.
+    ///
+    /// [`XmlLimit::MarkupRatio`]: crate::XmlLimit::MarkupRatio
+    pub fn max_markup_ratio(mut self, ratio: f64, min_elements: usize) -> Self {
+        self.max_markup_ratio = Some(MarkupRatio {
+            ratio,
+            min_elements,
+        });
+        self
+    }
+
+    /// Sets whether [`XmlRejection::InvalidXMLBodyAt`] rejections for bodies
+    /// that are not well-formed include a hexdump of the bytes surrounding the
+    /// first syntax error. Disabled by default as the hexdump includes the
+    /// request body in the response.
+    ///
+    /// Requires the `hexdump` feature.
+    ///
+    /// [`XmlRejection::InvalidXMLBodyAt`]: crate::XmlRejection::InvalidXMLBodyAt
+    #[cfg(feature = "hexdump")]
+    pub fn hexdump_errors(mut self, hexdump: bool) -> Self {
+        self.hexdump_errors = hexdump;
+        self
+    }
+
+    /// Registers a `callback` invoked with the rejection whenever extracting a
+    /// body fails, allowing rejections to be counted by any telemetry backend.
+    ///
+    /// ```
+    /// use axum_xml_up::XmlConfig;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::sync::Arc;
+    ///
+    /// let rejections = Arc::new(AtomicUsize::new(0));
+    /// let counter = rejections.clone();
+    /// let config = XmlConfig::new().on_rejection(move |_rejection| {
+    ///     counter.fetch_add(1, Ordering::Relaxed);
+    /// });
+    /// ```
+    pub fn on_rejection<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&XmlRejection) + Send + Sync + 'static,
+    {
+        self.on_rejection = Some(RejectionCallback(Arc::new(callback)));
+        self
+    }
+
+    /// Checks the raw request body against the digest sent in the `header`
+    /// before it is deserialized, for integrity-checked intake. The `digest`
+    /// function computes the expected header value from the body, such as the
+    /// hex encoded SHA-256 hash, allowing any hashing library and encoding to
+    /// be used. The header value is compared after trimming whitespace.
+    ///
+    /// Requests with a missing header or a mismatched digest are rejected with
+    /// [`XmlRejection::DigestMismatch`].
+    ///
+    /// ```
+    /// use axum::http::HeaderName;
+    /// use axum_xml_up::XmlConfig;
+    ///
+    /// fn checksum(body: &[u8]) -> String {
+    ///     // Any hash function, such as SHA-256 from the `sha2` crate
+    ///     body.iter().map(|&byte| u32::from(byte)).sum::<u32>().to_string()
+    /// }
+    ///
+    /// let config = XmlConfig::new().body_digest(HeaderName::from_static("x-body-digest"), checksum);
+    /// ```
+    pub fn body_digest<F>(mut self, header: HeaderName, digest: F) -> Self
+    where
+        F: Fn(&[u8]) -> String + Send + Sync + 'static,
+    {
+        self.body_digest = Some(BodyDigest {
+            header,
+            digest: Arc::new(digest),
+        });
+        self
+    }
+
+    /// Sets the maximum number of events the deserializer buffers while
+    /// collecting list items that are not next to each other, `None` removes
+    /// the limit. Documents needing a larger buffer are rejected.
+    ///
+    /// Defaults to `1024`, the size of the buffer does not affect throughput
+    /// for documents that fit within it (see the `event_buffer_size` benchmark)
+    /// so the default only bounds the memory an overlapped document can use.
+    ///
+    /// Requires the `overlapped-lists` feature.
+    #[cfg(feature = "overlapped-lists")]
+    pub fn event_buffer_size(mut self, size: Option<NonZeroUsize>) -> Self {
+        self.event_buffer_size = size;
+        self
+    }
+}
+
+/// Limit on the ratio of elements to content
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct MarkupRatio {
+    /// Maximum number of elements per byte of content
+    pub(crate) ratio: f64,
+    /// Number of elements a document can have before the ratio applies
+    pub(crate) min_elements: usize,
+}
+
+/// Predicate for accepted content types, see [`XmlConfig::accept_content_type`]
+#[derive(Clone)]
+pub(crate) struct ContentTypeMatcher(Arc<dyn Fn(&mime::Mime) -> bool + Send + Sync>);
+
+impl ContentTypeMatcher {
+    /// Whether the `mime` type is accepted
+    pub(crate) fn matches(&self, mime: &mime::Mime) -> bool {
+        (self.0)(mime)
+    }
+}
+
+impl fmt::Debug for ContentTypeMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ContentTypeMatcher")
+    }
+}
+
+/// Digest of the raw body checked against a header, see
+/// [`XmlConfig::body_digest`]
+#[derive(Clone)]
+pub(crate) struct BodyDigest {
+    /// Header containing the digest sent by the client
+    pub(crate) header: HeaderName,
+    /// Function computing the digest of the body
+    digest: Arc<DigestFn>,
+}
+
+/// Function computing the digest of a body
+type DigestFn = dyn Fn(&[u8]) -> String + Send + Sync;
+
+impl BodyDigest {
+    /// Checks that the digest of `body` matches the `expected` header value
+    pub(crate) fn verify(
+        &self,
+        expected: Option<&HeaderValue>,
+        body: &[u8],
+    ) -> Result<(), XmlRejection> {
+        let matches = expected
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|expected| expected.trim() == (self.digest)(body));
+
+        if matches {
+            Ok(())
+        } else {
+            Err(XmlRejection::DigestMismatch(self.header.to_string()))
+        }
+    }
+}
+
+impl fmt::Debug for BodyDigest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BodyDigest")
+            .field("header", &self.header)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Callback invoked for rejections, see [`XmlConfig::on_rejection`]
+#[derive(Clone)]
+pub(crate) struct RejectionCallback(Arc<dyn Fn(&XmlRejection) + Send + Sync>);
+
+impl fmt::Debug for RejectionCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RejectionCallback")
+    }
+}
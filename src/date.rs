@@ -0,0 +1,48 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Abbreviated day names starting from Sunday
+const DAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+
+/// Abbreviated month names starting from January
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Formats `time` as an HTTP date (`Sun, 06 Nov 1994 08:49:37 GMT`). Times
+/// before the unix epoch are written as the epoch.
+pub(crate) fn http_date(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let days = secs / 86_400;
+    let secs = secs % 86_400;
+
+    // The unix epoch was on a Thursday
+    let weekday = DAYS[((days + 4) % 7) as usize];
+
+    // Converts the days since the epoch into a civil date, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{weekday}, {day:02} {month} {year:04} {:02}:{:02}:{:02} GMT",
+        secs / 3_600,
+        secs / 60 % 60,
+        secs % 60,
+        month = MONTHS[(month - 1) as usize],
+    )
+}
@@ -0,0 +1,225 @@
+use crate::config::XmlConfig;
+use quick_xml::de::{Deserializer, EntityResolver};
+use quick_xml::events::attributes::Attribute;
+use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::{DeError, Reader, Writer};
+use serde::de::DeserializeOwned;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::convert::Infallible;
+
+/// Deserializes `bytes` into `T` using the provided `config`
+pub(crate) fn from_bytes<T>(bytes: &[u8], config: &XmlConfig) -> Result<T, DeError>
+where
+    T: DeserializeOwned,
+{
+    let mut bytes = Cow::Borrowed(bytes);
+    if config.normalize_line_endings && bytes.contains(&b'\r') {
+        bytes = Cow::Owned(normalize_line_endings(&bytes));
+    }
+    if config.trim_attribute_values {
+        bytes = Cow::Owned(trim_attribute_values(&bytes)?);
+    }
+    if config.unprefixed_attributes {
+        bytes = Cow::Owned(attributes_as_elements(&bytes)?);
+    }
+    if config.wrap_fragments {
+        bytes = Cow::Owned(wrap_fragment(&bytes));
+    }
+
+    // Transcoded bodies are read as UTF-8 regardless of the declared encoding
+    #[cfg(feature = "encoding")]
+    if let Ok(text) = std::str::from_utf8(&bytes) {
+        let mut deserializer =
+            Deserializer::from_str_with_resolver(text, ConfigEntityResolver(&config.entities));
+        #[cfg(feature = "overlapped-lists")]
+        deserializer.event_buffer_size(config.event_buffer_size);
+        return T::deserialize(&mut deserializer);
+    }
+
+    let mut deserializer =
+        Deserializer::with_resolver(bytes.as_ref(), ConfigEntityResolver(&config.entities));
+    #[cfg(feature = "overlapped-lists")]
+    deserializer.event_buffer_size(config.event_buffer_size);
+    T::deserialize(&mut deserializer)
+}
+
+/// Deserializes `T` from the document read from `reader` using the provided
+/// `config`, options that rewrite the document are not applied
+#[cfg(feature = "stream")]
+pub(crate) fn from_reader<T, R>(reader: R, config: &XmlConfig) -> Result<T, DeError>
+where
+    T: DeserializeOwned,
+    R: std::io::BufRead,
+{
+    let mut deserializer =
+        Deserializer::with_resolver(reader, ConfigEntityResolver(&config.entities));
+    #[cfg(feature = "overlapped-lists")]
+    deserializer.event_buffer_size(config.event_buffer_size);
+    T::deserialize(&mut deserializer)
+}
+
+/// Replaces the `\r\n` and lone `\r` line endings in `bytes` with `\n`, the
+/// same as an XML processor is required to before parsing
+fn normalize_line_endings(bytes: &[u8]) -> Vec<u8> {
+    let mut normalized = Vec::with_capacity(bytes.len());
+    let mut bytes = bytes.iter().copied().peekable();
+
+    while let Some(byte) = bytes.next() {
+        if byte == b'\r' {
+            bytes.next_if_eq(&b'\n');
+            normalized.push(b'\n');
+        } else {
+            normalized.push(byte);
+        }
+    }
+
+    normalized
+}
+
+/// Rewrites the document in `bytes` trimming the leading and trailing
+/// whitespace from all attribute values, other events are written unchanged
+fn trim_attribute_values(bytes: &[u8]) -> Result<Vec<u8>, DeError> {
+    let mut reader = Reader::from_reader(bytes);
+    let mut writer = Writer::new(Vec::with_capacity(bytes.len()));
+
+    loop {
+        let event = match reader.read_event()? {
+            Event::Start(start) => Event::Start(trim_attributes(&start)?),
+            Event::Empty(start) => Event::Empty(trim_attributes(&start)?),
+            Event::Eof => break,
+            event => event,
+        };
+        writer.write_event(event)?;
+    }
+
+    Ok(writer.into_inner())
+}
+
+/// Creates a copy of `start` with its attribute values trimmed
+fn trim_attributes(start: &BytesStart) -> Result<BytesStart<'static>, DeError> {
+    let mut trimmed = start.to_owned();
+    trimmed.clear_attributes();
+
+    // Duplicate attributes are rejected when deserializing, checking for them
+    // here would be quadratic in the number of attributes
+    for attribute in start.attributes().with_checks(false) {
+        let attribute = attribute.map_err(quick_xml::Error::from)?;
+        let value = attribute.value.trim_ascii();
+
+        // Values are always written with double quotes
+        let value = if value.contains(&b'"') {
+            Cow::Owned(value.iter().fold(Vec::new(), |mut value, &byte| {
+                match byte {
+                    b'"' => value.extend_from_slice(b"&quot;"),
+                    byte => value.push(byte),
+                }
+                value
+            }))
+        } else {
+            Cow::Borrowed(value)
+        };
+
+        trimmed.push_attribute(Attribute {
+            key: attribute.key,
+            value,
+        });
+    }
+
+    Ok(trimmed)
+}
+
+/// Rewrites the document in `bytes` adding a child element for each attribute
+/// of every element, so attributes can be deserialized into fields without the
+/// `@` prefix. The attributes themselves are kept.
+fn attributes_as_elements(bytes: &[u8]) -> Result<Vec<u8>, DeError> {
+    let mut reader = Reader::from_reader(bytes);
+    let mut writer = Writer::new(Vec::with_capacity(bytes.len() * 2));
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(start) => {
+                writer.write_event(Event::Start(start.borrow()))?;
+                write_attribute_elements(&mut writer, &start)?;
+            }
+            // Empty elements need an end tag to contain the attribute elements
+            Event::Empty(start) => {
+                writer.write_event(Event::Start(start.borrow()))?;
+                write_attribute_elements(&mut writer, &start)?;
+                writer.write_event(Event::End(start.to_end()))?;
+            }
+            Event::Eof => break,
+            event => writer.write_event(event)?,
+        }
+    }
+
+    Ok(writer.into_inner())
+}
+
+/// Writes a child element for each attribute of `start` containing the
+/// attribute value, namespace declarations are skipped
+fn write_attribute_elements(
+    writer: &mut Writer<Vec<u8>>,
+    start: &BytesStart,
+) -> Result<(), DeError> {
+    // Duplicate attributes are rejected when deserializing, checking for them
+    // here would be quadratic in the number of attributes
+    for attribute in start.attributes().with_checks(false) {
+        let attribute = attribute.map_err(quick_xml::Error::from)?;
+        if attribute.key.as_namespace_binding().is_some() {
+            continue;
+        }
+
+        let name = std::str::from_utf8(attribute.key.as_ref()).map_err(quick_xml::Error::from)?;
+        writer.write_event(Event::Start(BytesStart::new(name)))?;
+        // Attribute values are already escaped
+        writer.write_event(Event::Text(BytesText::from_escaped(
+            std::str::from_utf8(&attribute.value).map_err(quick_xml::Error::from)?,
+        )))?;
+        writer.write_event(Event::End(BytesEnd::new(name)))?;
+    }
+
+    Ok(())
+}
+
+/// Wraps the fragment in `bytes` with a synthetic root element, the root is
+/// placed after the XML declaration when one is present
+fn wrap_fragment(bytes: &[u8]) -> Vec<u8> {
+    const START: &[u8] = b"<fragment>";
+    const END: &[u8] = b"</fragment>";
+
+    let start = bytes.iter().position(|byte| !byte.is_ascii_whitespace());
+    let declaration_end = start
+        .filter(|&start| bytes[start..].starts_with(b"<?xml"))
+        .and_then(|start| {
+            bytes[start..]
+                .windows(2)
+                .position(|window| window == b"?>")
+                .map(|end| start + end + 2)
+        })
+        .unwrap_or(0);
+
+    let (declaration, fragment) = bytes.split_at(declaration_end);
+    let mut wrapped = Vec::with_capacity(bytes.len() + START.len() + END.len());
+    wrapped.extend_from_slice(declaration);
+    wrapped.extend_from_slice(START);
+    wrapped.extend_from_slice(fragment);
+    wrapped.extend_from_slice(END);
+    wrapped
+}
+
+/// Entity resolver that only resolves the entities registered on the config,
+/// any entities declared by the document DTD are ignored
+struct ConfigEntityResolver<'a>(&'a HashMap<String, String>);
+
+impl EntityResolver for ConfigEntityResolver<'_> {
+    type Error = Infallible;
+
+    fn capture(&mut self, _doctype: BytesText) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn resolve(&self, entity: &str) -> Option<&str> {
+        self.0.get(entity).map(String::as_str)
+    }
+}
@@ -0,0 +1,148 @@
+use crate::rejection::XmlRejection;
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+use quick_xml::events::Event;
+use quick_xml::{DeError, Reader};
+use std::borrow::Cow;
+use std::ops::Range;
+
+/// UTF-8 byte order mark kept at the start of UTF-8 bodies
+const BYTE_ORDER_MARK: &[u8] = b"\xEF\xBB\xBF";
+
+/// Transcodes the document in `bytes` into UTF-8. The encoding is chosen from
+/// the byte order mark, then the `charset` parameter of the content type and
+/// then the `encoding` of the XML declaration, defaulting to UTF-8.
+///
+/// UTF-8 bodies are returned unchanged, including any UTF-8 byte order mark
+/// so it can be checked by [`XmlConfig::reject_byte_order_mark`]. The
+/// `encoding` of the XML declaration is replaced with UTF-8 when it names
+/// another encoding, so readers don't decode the body a second time.
+///
+/// [`XmlConfig::reject_byte_order_mark`]: crate::XmlConfig::reject_byte_order_mark
+pub(crate) fn decode<'a>(
+    bytes: &'a [u8],
+    charset: Option<&str>,
+) -> Result<Cow<'a, [u8]>, XmlRejection> {
+    let (encoding, bom_len) = match Encoding::for_bom(bytes) {
+        Some(found) => found,
+        None => (detect(bytes, charset)?, 0),
+    };
+
+    if encoding == UTF_8 {
+        return Ok(declare_utf8(Cow::Borrowed(bytes)));
+    }
+
+    let text = encoding
+        .decode_without_bom_handling_and_without_replacement(&bytes[bom_len..])
+        .ok_or_else(|| DeError::from(quick_xml::Error::NonDecodable(None)))?;
+    Ok(declare_utf8(Cow::Owned(text.into_owned().into_bytes())))
+}
+
+/// Replaces the `encoding` of the XML declaration in the UTF-8 `bytes` with
+/// UTF-8 when it names a different encoding
+fn declare_utf8(bytes: Cow<'_, [u8]>) -> Cow<'_, [u8]> {
+    let Some(range) = declared_encoding_range(&bytes) else {
+        return bytes;
+    };
+    if Encoding::for_label(&bytes[range.clone()]) == Some(UTF_8) {
+        return bytes;
+    }
+
+    let mut declared = Vec::with_capacity(bytes.len());
+    declared.extend_from_slice(&bytes[..range.start]);
+    declared.extend_from_slice(b"UTF-8");
+    declared.extend_from_slice(&bytes[range.end..]);
+    Cow::Owned(declared)
+}
+
+/// Finds the range of the `encoding` value of the XML declaration at the
+/// start of `bytes`, after any UTF-8 byte order mark and whitespace
+fn declared_encoding_range(bytes: &[u8]) -> Option<Range<usize>> {
+    let trimmed = bytes
+        .strip_prefix(BYTE_ORDER_MARK)
+        .unwrap_or(bytes)
+        .trim_ascii_start();
+    if !trimmed.starts_with(b"<?xml") {
+        return None;
+    }
+
+    let start = bytes.len() - trimmed.len();
+    let end = start + find(&bytes[start..], b"?>")?;
+    let declaration = &bytes[..end];
+    let name = start + find(&declaration[start..], b"encoding")? + b"encoding".len();
+
+    let value = declaration[name..]
+        .trim_ascii_start()
+        .strip_prefix(b"=")?
+        .trim_ascii_start();
+    let (&quote, value) = value.split_first()?;
+    if !matches!(quote, b'"' | b'\'') {
+        return None;
+    }
+
+    // The value is the end of the declaration after the opening quote
+    let offset = end - value.len();
+    let len = value.iter().position(|b| *b == quote)?;
+    Some(offset..offset + len)
+}
+
+/// Finds the offset of the first occurrence of `needle` in `haystack`
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Determines the encoding of `bytes` without a byte order mark
+fn detect(bytes: &[u8], charset: Option<&str>) -> Result<&'static Encoding, XmlRejection> {
+    if let Some(charset) = charset {
+        return Encoding::for_label(charset.trim().as_bytes())
+            .ok_or_else(|| XmlRejection::UnsupportedEncoding(charset.to_string()));
+    }
+
+    // UTF-16 declarations can't be read before the body is decoded
+    if bytes.starts_with(&[0x00, b'<', 0x00, b'?']) {
+        return Ok(UTF_16BE);
+    }
+    if bytes.starts_with(&[b'<', 0x00, b'?', 0x00]) {
+        return Ok(UTF_16LE);
+    }
+
+    let Some(label) = declared_encoding(bytes) else {
+        return Ok(UTF_8);
+    };
+
+    match Encoding::for_label(&label) {
+        // A UTF-16 label in a document that was readable as ASCII is wrong,
+        // the body is read the same way as without the label
+        Some(encoding) if encoding == UTF_16BE || encoding == UTF_16LE => Ok(UTF_8),
+        Some(encoding) => Ok(encoding),
+        None => Err(XmlRejection::UnsupportedEncoding(
+            String::from_utf8_lossy(&label).into_owned(),
+        )),
+    }
+}
+
+/// Reads the `encoding` of the XML declaration at the start of `bytes`
+fn declared_encoding(bytes: &[u8]) -> Option<Vec<u8>> {
+    let mut reader = Reader::from_reader(bytes);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Decl(decl)) => {
+                return decl.encoding().and_then(Result::ok).map(Cow::into_owned);
+            }
+            // Leading whitespace is allowed by the reader
+            Ok(Event::Text(text)) if text.iter().all(u8::is_ascii_whitespace) => {}
+            _ => return None,
+        }
+    }
+}
+
+/// Creates a reader for `bytes` that has been transcoded by [`decode`]. The
+/// encoding named by the XML declaration is ignored as the bytes are already
+/// UTF-8, bodies that are not valid UTF-8 are read as they are.
+pub(crate) fn reader(bytes: &[u8]) -> Reader<&[u8]> {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Reader::from_str(text),
+        Err(_) => Reader::from_reader(bytes),
+    }
+}
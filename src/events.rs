@@ -0,0 +1,119 @@
+use crate::config::XmlConfig;
+use crate::rejection::XmlRejection;
+use axum_core::body::Body;
+use axum_core::extract::{FromRequest, Request};
+use bytes::Bytes;
+use core::pin::Pin;
+use quick_xml::events::Event;
+use quick_xml::{DeError, Reader};
+use std::fmt;
+use std::future::Future;
+use std::io::Cursor;
+
+/// XML Extractor for the events of the body, for handlers that walk the
+/// document manually instead of deserializing it
+///
+/// Checks the body the same way as [`RawXml`](crate::RawXml) and then parses
+/// each event as the iterator is advanced. The events are owned so they can
+/// be kept after the iterator moves on. Iteration ends at the end of the
+/// document and stops after the first error.
+///
+/// ```rust,no_run
+/// use axum_xml_up::XmlEvents;
+/// use quick_xml::events::Event;
+///
+/// async fn count_elements(events: XmlEvents) -> String {
+///     let count = events
+///         .filter(|event| matches!(event, Ok(Event::Start(_) | Event::Empty(_))))
+///         .count();
+///     count.to_string()
+/// }
+/// ```
+pub struct XmlEvents {
+    /// Reader over the request body
+    reader: Reader<Cursor<Bytes>>,
+    /// Buffer the current event is read into
+    buf: Vec<u8>,
+    /// Whether the end of the body was reached or an error occurred
+    finished: bool,
+    /// Config used to report errors
+    config: XmlConfig,
+}
+
+impl XmlEvents {
+    /// Creates an iterator over the events of the document in `bytes` that has
+    /// already been checked against the `config`
+    fn new(bytes: Bytes, config: XmlConfig) -> Self {
+        Self {
+            reader: Reader::from_reader(Cursor::new(bytes)),
+            buf: Vec::new(),
+            finished: false,
+            config,
+        }
+    }
+}
+
+impl fmt::Debug for XmlEvents {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("XmlEvents")
+            .field("position", &self.reader.buffer_position())
+            .field("finished", &self.finished)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Iterator for XmlEvents {
+    type Item = Result<Event<'static>, XmlRejection>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        self.buf.clear();
+        match self.reader.read_event_into(&mut self.buf) {
+            Ok(Event::Eof) => {
+                self.finished = true;
+                None
+            }
+            Ok(event) => Some(Ok(event.into_owned())),
+            Err(err) => {
+                self.finished = true;
+                let err = XmlRejection::from(DeError::from(err));
+                Some(self.config.report(Err(err)))
+            }
+        }
+    }
+}
+
+impl<S> FromRequest<S> for XmlEvents
+where
+    S: Send + Sync + 'static,
+{
+    type Rejection = XmlRejection;
+
+    fn from_request<'state, 'future>(
+        mut req: Request<Body>,
+        state: &'state S,
+    ) -> Pin<Box<dyn Future<Output = Result<Self, Self::Rejection>> + Send + 'future>>
+    where
+        'state: 'future,
+        Self: 'future,
+    {
+        Box::pin(async move {
+            let config = XmlConfig::resolve(&mut req, state);
+            let result = async {
+                let mut bytes = crate::read_body(req, state, &config).await?;
+
+                let len = crate::strip_byte_order_mark(&bytes, &config)?.len();
+                bytes = bytes.split_off(bytes.len() - len);
+
+                crate::validate(&bytes, &config)?;
+                Ok(bytes)
+            };
+
+            let bytes = config.report(result.await)?;
+            Ok(Self::new(bytes, config))
+        })
+    }
+}
@@ -0,0 +1,606 @@
+use crate::config::XmlConfig;
+use crate::rejection::{XmlErrorKind, XmlRejection};
+use crate::value::{XmlNode, XmlValue};
+use crate::Xml;
+use axum_core::body::Body;
+use axum_core::extract::{FromRequest, Request};
+use core::pin::Pin;
+use http::HeaderMap;
+use quick_xml::events::Event;
+use quick_xml::DeError;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// XML Extractor that also provides the request headers
+///
+/// Deserializes the body the same way as [`Xml`] while keeping a copy of the
+/// request headers, avoiding the need for a separate [`HeaderMap`] extractor.
+///
+/// ```rust,no_run
+/// use axum::{routing::post, Router};
+/// use axum_xml_up::XmlWithHeaders;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct CreateUser {
+///     email: String,
+/// }
+///
+/// async fn create_user(XmlWithHeaders { value, headers }: XmlWithHeaders<CreateUser>) {
+///     // value is a `CreateUser` and headers are the request headers
+/// }
+///
+/// let app: Router = Router::new().route("/users", post(create_user));
+/// ```
+#[derive(Debug, Clone)]
+pub struct XmlWithHeaders<T> {
+    /// The deserialized request body
+    pub value: T,
+    /// The request headers
+    pub headers: HeaderMap,
+}
+
+impl<T, S> FromRequest<S> for XmlWithHeaders<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync + 'static,
+{
+    type Rejection = XmlRejection;
+
+    fn from_request<'state, 'future>(
+        req: Request<Body>,
+        state: &'state S,
+    ) -> Pin<Box<dyn Future<Output = Result<Self, Self::Rejection>> + Send + 'future>>
+    where
+        'state: 'future,
+        Self: 'future,
+    {
+        Box::pin(async move {
+            // Headers are captured before the request is consumed
+            let headers = req.headers().clone();
+            let Xml(value) = Xml::from_request(req, state).await?;
+
+            Ok(Self { value, headers })
+        })
+    }
+}
+
+/// XML Extractor that also reports how long the body took to parse
+///
+/// Deserializes the body the same way as [`Xml`], timing the checks and
+/// deserialization of the body. Reading the body is not included in the
+/// duration.
+///
+/// ```rust,no_run
+/// use axum_xml_up::TimedXml;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct CreateUser {
+///     email: String,
+/// }
+///
+/// async fn create_user(TimedXml { value, parse_duration }: TimedXml<CreateUser>) {
+///     // parse_duration is the time spent deserializing value
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TimedXml<T> {
+    /// The deserialized request body
+    pub value: T,
+    /// Time taken to parse the request body
+    pub parse_duration: Duration,
+}
+
+impl<T, S> FromRequest<S> for TimedXml<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync + 'static,
+{
+    type Rejection = XmlRejection;
+
+    fn from_request<'state, 'future>(
+        mut req: Request<Body>,
+        state: &'state S,
+    ) -> Pin<Box<dyn Future<Output = Result<Self, Self::Rejection>> + Send + 'future>>
+    where
+        'state: 'future,
+        Self: 'future,
+    {
+        Box::pin(async move {
+            let config = XmlConfig::resolve(&mut req, state);
+            let result = async {
+                let bytes = crate::read_body(req, state, &config).await?;
+
+                let start = Instant::now();
+                let value = crate::parse(&bytes, &config)?;
+                let parse_duration = start.elapsed();
+
+                Ok(Self {
+                    value,
+                    parse_duration,
+                })
+            };
+
+            config.report(result.await)
+        })
+    }
+}
+
+/// XML Extractor that also reports details about the request content type
+///
+/// Deserializes the body the same way as [`Xml`], useful for diagnosing
+/// encoding issues from clients.
+///
+/// ```rust,no_run
+/// use axum_xml_up::XmlWithMeta;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct CreateUser {
+///     email: String,
+/// }
+///
+/// async fn create_user(XmlWithMeta { value, meta }: XmlWithMeta<CreateUser>) {
+///     // meta.charset is the charset parameter of the content type
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct XmlWithMeta<T> {
+    /// The deserialized request body
+    pub value: T,
+    /// Details about the request content type
+    pub meta: XmlMeta,
+}
+
+/// Details about the content type of a request, see [`XmlWithMeta`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct XmlMeta {
+    /// Whether the content type had a `charset` parameter
+    pub had_charset: bool,
+    /// Value of the `charset` parameter, in lowercase
+    pub charset: Option<String>,
+}
+
+impl<T, S> FromRequest<S> for XmlWithMeta<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync + 'static,
+{
+    type Rejection = XmlRejection;
+
+    fn from_request<'state, 'future>(
+        mut req: Request<Body>,
+        state: &'state S,
+    ) -> Pin<Box<dyn Future<Output = Result<Self, Self::Rejection>> + Send + 'future>>
+    where
+        'state: 'future,
+        Self: 'future,
+    {
+        Box::pin(async move {
+            let config = XmlConfig::resolve(&mut req, state);
+
+            // The content type is captured before the request is consumed
+            let charset = crate::content_type(&req, &config.content_type_header)
+                .and_then(|mime| mime.get_param(mime::CHARSET).map(|value| value.to_string()));
+            let meta = XmlMeta {
+                had_charset: charset.is_some(),
+                charset,
+            };
+
+            let result = async {
+                let bytes = crate::read_body(req, state, &config).await?;
+                let value = crate::parse(&bytes, &config)?;
+                Ok(Self { value, meta })
+            };
+
+            config.report(result.await)
+        })
+    }
+}
+
+/// XML Extractor that also provides the attributes of the root element
+///
+/// Deserializes the body the same way as [`Xml`] and collects the attributes
+/// of the root element by their qualified name, for routing or authorization
+/// decisions based on attributes such as a `tenant`. Namespace declarations
+/// are included as `xmlns` attributes.
+///
+/// ```rust,no_run
+/// use axum_xml_up::XmlWithAttributes;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct CreateUser {
+///     email: String,
+/// }
+///
+/// async fn create_user(XmlWithAttributes { value, attributes }: XmlWithAttributes<CreateUser>) {
+///     // <CreateUser tenant="acme"> provides attributes["tenant"] == "acme"
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct XmlWithAttributes<T> {
+    /// The deserialized request body
+    pub value: T,
+    /// The attributes of the root element with their values unescaped
+    pub attributes: HashMap<String, String>,
+}
+
+impl<T, S> FromRequest<S> for XmlWithAttributes<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync + 'static,
+{
+    type Rejection = XmlRejection;
+
+    fn from_request<'state, 'future>(
+        mut req: Request<Body>,
+        state: &'state S,
+    ) -> Pin<Box<dyn Future<Output = Result<Self, Self::Rejection>> + Send + 'future>>
+    where
+        'state: 'future,
+        Self: 'future,
+    {
+        Box::pin(async move {
+            let config = XmlConfig::resolve(&mut req, state);
+            let result = async {
+                let bytes = crate::read_body(req, state, &config).await?;
+                let value = crate::parse(&bytes, &config)?;
+                let attributes = root_attributes(&bytes, &config)?;
+                Ok(Self { value, attributes })
+            };
+
+            config.report(result.await)
+        })
+    }
+}
+
+/// Collects the attributes of the root element of the document in `bytes`,
+/// expanding the entities registered in the `config`
+fn root_attributes(bytes: &[u8], config: &XmlConfig) -> Result<HashMap<String, String>, DeError> {
+    #[cfg(feature = "encoding")]
+    let mut reader = crate::encoding::reader(bytes);
+    #[cfg(not(feature = "encoding"))]
+    let mut reader = quick_xml::Reader::from_reader(bytes);
+    let start = loop {
+        match reader.read_event()? {
+            Event::Start(start) | Event::Empty(start) => break start,
+            Event::Eof => return Ok(HashMap::new()),
+            _ => {}
+        }
+    };
+
+    let mut attributes = HashMap::new();
+    for attribute in start.attributes() {
+        let attribute = attribute.map_err(quick_xml::Error::from)?;
+        let name = reader
+            .decoder()
+            .decode(attribute.key.as_ref())?
+            .into_owned();
+        let value = attribute
+            .decode_and_unescape_value_with(&reader, |entity| {
+                config.entities.get(entity).map(String::as_str)
+            })?
+            .into_owned();
+        attributes.insert(name, value);
+    }
+    Ok(attributes)
+}
+
+/// XML Extractor that recovers from a single invalid section of the body
+///
+/// Deserializes the body the same way as [`Xml`]. When the body is well-formed
+/// but a value could not be converted into the expected type, each child
+/// element of the root element is removed in turn until the rest of the body
+/// deserializes. The removed section is reported as a [`XmlWarning`] and the
+/// field it was deserialized into should use a default, such as an `Option`
+/// or a `#[serde(default)]` field. Bodies that can't be recovered by removing
+/// a single element are rejected with the original error.
+///
+/// Each attempt deserializes the rest of the body again, so only the first 16
+/// child elements are removed to bound the cost of recovering large bodies.
+///
+/// ```rust,no_run
+/// use axum_xml_up::PartialXml;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Import {
+///     name: String,
+///     age: Option<u32>,
+/// }
+///
+/// async fn import(PartialXml { value, warning }: PartialXml<Import>) {
+///     // <Import><name>Jacob</name><age>unknown</age></Import> provides an
+///     // `age` of `None` with a warning for the `age` element
+/// }
+/// ```
+#[derive(Debug)]
+pub struct PartialXml<T> {
+    /// The deserialized request body
+    pub value: T,
+    /// The section of the body that was removed to deserialize the value
+    pub warning: Option<XmlWarning>,
+}
+
+/// Section of a body removed by [`PartialXml`]
+#[derive(Debug)]
+pub struct XmlWarning {
+    /// Name of the child element of the root that was removed
+    pub element: String,
+    /// The error deserializing the whole body
+    pub error: XmlRejection,
+}
+
+impl<T, S> FromRequest<S> for PartialXml<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync + 'static,
+{
+    type Rejection = XmlRejection;
+
+    fn from_request<'state, 'future>(
+        mut req: Request<Body>,
+        state: &'state S,
+    ) -> Pin<Box<dyn Future<Output = Result<Self, Self::Rejection>> + Send + 'future>>
+    where
+        'state: 'future,
+        Self: 'future,
+    {
+        Box::pin(async move {
+            let config = XmlConfig::resolve(&mut req, state);
+            let result = async {
+                let bytes = crate::read_body(req, state, &config).await?;
+                let error = match crate::parse(&bytes, &config) {
+                    Ok(value) => {
+                        return Ok(Self {
+                            value,
+                            warning: None,
+                        })
+                    }
+                    Err(error) => error,
+                };
+
+                match recover(&bytes, &config, &error) {
+                    Some((value, element)) => Ok(Self {
+                        value,
+                        warning: Some(XmlWarning { element, error }),
+                    }),
+                    None => Err(error),
+                }
+            };
+
+            config.report(result.await)
+        })
+    }
+}
+
+/// Maximum number of child elements [`PartialXml`] attempts to remove, bounding
+/// the cost of recovering large bodies
+const MAX_RECOVERY_ATTEMPTS: usize = 16;
+
+/// Attempts to deserialize the document in `bytes` with one of the first
+/// [`MAX_RECOVERY_ATTEMPTS`] child elements of the root removed, providing the
+/// value and the name of the removed element
+fn recover<T>(bytes: &[u8], config: &XmlConfig, error: &XmlRejection) -> Option<(T, String)>
+where
+    T: DeserializeOwned,
+{
+    // Only values that failed to convert can be removed, other errors such as
+    // syntax errors or limits apply to the whole document
+    let recoverable = matches!(
+        error.kind(),
+        XmlErrorKind::TypeMismatch | XmlErrorKind::Other
+    );
+    if !recoverable || error.as_deserialize_error().is_none() {
+        return None;
+    }
+
+    let bytes = crate::strip_byte_order_mark(bytes, config).ok()?;
+    let mut root = XmlValue::from_bytes(bytes).ok()?;
+
+    let elements: Vec<(usize, String)> = root
+        .children
+        .iter()
+        .enumerate()
+        .filter_map(|(index, child)| match child {
+            XmlNode::Element(element) => Some((index, element.name.clone())),
+            _ => None,
+        })
+        .take(MAX_RECOVERY_ATTEMPTS)
+        .collect();
+
+    for (index, name) in elements {
+        // The document was already checked so only needs to be deserialized
+        let removed = root.children.remove(index);
+        let result = crate::de::from_bytes(&root.to_bytes(), config);
+        root.children.insert(index, removed);
+
+        if let Ok(value) = result {
+            return Some((value, name));
+        }
+    }
+
+    None
+}
+
+/// XML Extractor for an optional body
+///
+/// Requests without a body, or with an empty body, are extracted as `None`
+/// without checking their content type. Other bodies are checked and
+/// deserialized the same way as [`Xml`], rejecting bodies that are invalid.
+///
+/// Extracting `Option<Xml<T>>` instead uses the implementation from axum for
+/// all extractors, which produces `None` for invalid bodies as well.
+///
+/// ```rust,no_run
+/// use axum_xml_up::OptionalXml;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Filter {
+///     name: String,
+/// }
+///
+/// async fn list_users(OptionalXml(filter): OptionalXml<Filter>) {
+///     // filter is `None` when no body was sent
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OptionalXml<T>(pub Option<T>);
+
+impl<T, S> FromRequest<S> for OptionalXml<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync + 'static,
+{
+    type Rejection = XmlRejection;
+
+    fn from_request<'state, 'future>(
+        mut req: Request<Body>,
+        state: &'state S,
+    ) -> Pin<Box<dyn Future<Output = Result<Self, Self::Rejection>> + Send + 'future>>
+    where
+        'state: 'future,
+        Self: 'future,
+    {
+        Box::pin(async move {
+            let config = XmlConfig::resolve(&mut req, state);
+            let result = async {
+                // The content type only applies when there is a body
+                let content_type = crate::check_content_type(&req, &config);
+                let bytes = crate::read_body_unchecked(req, state, &config).await?;
+                if bytes.is_empty() {
+                    return Ok(Self(None));
+                }
+
+                content_type?;
+                crate::parse(&bytes, &config).map(|value| Self(Some(value)))
+            };
+
+            config.report(result.await)
+        })
+    }
+}
+
+/// Extractor for the raw text of an XML body
+///
+/// Checks the content type and that the body is well-formed XML the same way
+/// as [`Xml`] without deserializing it, for bodies that are forwarded, hashed
+/// or validated against a schema. The text is the original body, options that
+/// rewrite the document before it is deserialized such as
+/// [`XmlConfig::repair_malformed`] don't apply.
+///
+/// ```rust,no_run
+/// use axum_xml_up::RawXml;
+///
+/// async fn forward(RawXml(body): RawXml) {
+///     // body is the well-formed XML text of the request
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RawXml(pub String);
+
+impl<S> FromRequest<S> for RawXml
+where
+    S: Send + Sync + 'static,
+{
+    type Rejection = XmlRejection;
+
+    fn from_request<'state, 'future>(
+        mut req: Request<Body>,
+        state: &'state S,
+    ) -> Pin<Box<dyn Future<Output = Result<Self, Self::Rejection>> + Send + 'future>>
+    where
+        'state: 'future,
+        Self: 'future,
+    {
+        Box::pin(async move {
+            let config = XmlConfig::resolve(&mut req, state);
+            let result = async {
+                let bytes = crate::read_body(req, state, &config).await?;
+                crate::validate(&bytes, &config)?;
+
+                let text = String::from_utf8(bytes.to_vec()).map_err(|err| {
+                    DeError::from(quick_xml::Error::NonDecodable(Some(err.utf8_error())))
+                })?;
+                Ok(Self(text))
+            };
+
+            config.report(result.await)
+        })
+    }
+}
+
+/// Extractor accepting either an XML or a form encoded body
+///
+/// Bodies with a content type accepted by the [`XmlConfig`] are deserialized
+/// the same way as [`Xml`], any other body is deserialized as
+/// `application/x-www-form-urlencoded` into the same type. Both are read with
+/// the body options of the config such as [`XmlConfig::max_body_size`].
+/// Useful while migrating endpoints from form posts to XML.
+///
+/// Requires the `form` feature.
+///
+/// ```rust,no_run
+/// use axum::{routing::post, Router};
+/// use axum_xml_up::XmlOrForm;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct CreateUser {
+///     email: String,
+/// }
+///
+/// async fn create_user(XmlOrForm(payload): XmlOrForm<CreateUser>) {
+///     // payload is a `CreateUser` from either format
+/// }
+///
+/// let app: Router = Router::new().route("/users", post(create_user));
+/// ```
+#[cfg(feature = "form")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct XmlOrForm<T>(pub T);
+
+#[cfg(feature = "form")]
+impl<T, S> FromRequest<S> for XmlOrForm<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync + 'static,
+{
+    type Rejection = XmlRejection;
+
+    fn from_request<'state, 'future>(
+        mut req: Request<Body>,
+        state: &'state S,
+    ) -> Pin<Box<dyn Future<Output = Result<Self, Self::Rejection>> + Send + 'future>>
+    where
+        'state: 'future,
+        Self: 'future,
+    {
+        Box::pin(async move {
+            let config = XmlConfig::resolve(&mut req, state);
+            let result = async {
+                // Form bodies stay forms when the XML content type isn't required
+                let form =
+                    crate::content_type(&req, &config.content_type_header).is_some_and(|mime| {
+                        mime.essence_str() == mime::APPLICATION_WWW_FORM_URLENCODED.essence_str()
+                    });
+                let xml = !form && crate::check_content_type(&req, &config).is_ok();
+
+                let bytes = crate::read_body_unchecked(req, state, &config).await?;
+                if xml {
+                    return crate::parse(&bytes, &config).map(Self);
+                }
+
+                let value = serde_urlencoded::from_bytes(&bytes)?;
+                Ok(Self(value))
+            };
+
+            config.report(result.await)
+        })
+    }
+}
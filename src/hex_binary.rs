@@ -0,0 +1,109 @@
+//! Serde helpers for `xsd:hexBinary` values containing hex encoded bytes
+//!
+//! Text content or attributes such as `<checksum>0FA3</checksum>` can be
+//! deserialized into a `Vec<u8>`, both uppercase and lowercase digits are
+//! accepted. The [`lower`] and [`upper`] modules can be used with
+//! `#[serde(with = "..")]` choosing the case of the serialized digits:
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Deserialize, Serialize)]
+//! struct Upload {
+//!     #[serde(with = "axum_xml_up::hex_binary::upper")]
+//!     checksum: Vec<u8>,
+//! }
+//! ```
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serializer};
+
+/// Deserializes a hex encoded value into bytes, surrounding whitespace is
+/// ignored
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    let value = value.trim().as_bytes();
+    if value.len() % 2 != 0 {
+        return Err(D::Error::custom("hexBinary value must have an even length"));
+    }
+
+    value
+        .chunks_exact(2)
+        .map(|pair| match (digit(pair[0]), digit(pair[1])) {
+            (Some(high), Some(low)) => Ok((high << 4) | low),
+            _ => Err(D::Error::custom(
+                "hexBinary value contains an invalid digit",
+            )),
+        })
+        .collect()
+}
+
+/// Serializes bytes as a hex encoded value, using uppercase digits when
+/// `UPPER` is set
+pub fn serialize<S, const UPPER: bool>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let digits = if UPPER {
+        b"0123456789ABCDEF"
+    } else {
+        b"0123456789abcdef"
+    };
+
+    let mut value = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        value.push(digits[(byte >> 4) as usize] as char);
+        value.push(digits[(byte & 0xF) as usize] as char);
+    }
+    serializer.serialize_str(&value)
+}
+
+/// Value of a single hex digit
+fn digit(byte: u8) -> Option<u8> {
+    (byte as char).to_digit(16).map(|digit| digit as u8)
+}
+
+/// Hex encoded values serialized with lowercase digits (`"0fa3"`)
+pub mod lower {
+    use super::*;
+
+    /// Deserializes a hex encoded value, see [`super::deserialize`]
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::deserialize(deserializer)
+    }
+
+    /// Serializes bytes with lowercase digits, see [`super::serialize`]
+    pub fn serialize<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        super::serialize::<S, false>(bytes, serializer)
+    }
+}
+
+/// Hex encoded values serialized with uppercase digits (`"0FA3"`)
+pub mod upper {
+    use super::*;
+
+    /// Deserializes a hex encoded value, see [`super::deserialize`]
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::deserialize(deserializer)
+    }
+
+    /// Serializes bytes with uppercase digits, see [`super::serialize`]
+    pub fn serialize<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        super::serialize::<S, true>(bytes, serializer)
+    }
+}
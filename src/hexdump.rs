@@ -0,0 +1,43 @@
+use std::fmt::Write;
+
+/// Number of bytes shown on each line of the hexdump
+const LINE_LEN: usize = 16;
+/// Number of lines included before and after the line containing the failure
+const CONTEXT_LINES: usize = 1;
+
+/// Creates a hexdump of the lines of `bytes` around `offset`, each line shows
+/// the address, the bytes in hex, and the printable ascii characters
+pub(crate) fn hexdump(bytes: &[u8], offset: usize) -> String {
+    let line = offset / LINE_LEN;
+    let start = line.saturating_sub(CONTEXT_LINES) * LINE_LEN;
+    let end = ((line + CONTEXT_LINES + 1) * LINE_LEN).min(bytes.len());
+
+    let mut out = String::new();
+    for (index, chunk) in bytes[start..end].chunks(LINE_LEN).enumerate() {
+        let address = start + index * LINE_LEN;
+        let _ = write!(out, "{address:08x} ");
+        for position in 0..LINE_LEN {
+            match chunk.get(position) {
+                // Mark the byte at the failure offset
+                Some(byte) if address + position == offset => {
+                    let _ = write!(out, ">{byte:02x}");
+                }
+                Some(byte) => {
+                    let _ = write!(out, " {byte:02x}");
+                }
+                None => out.push_str("   "),
+            }
+        }
+
+        out.push_str("  |");
+        out.extend(chunk.iter().map(|&byte| {
+            if byte.is_ascii_graphic() || byte == b' ' {
+                byte as char
+            } else {
+                '.'
+            }
+        }));
+        out.push_str("|\n");
+    }
+    out
+}
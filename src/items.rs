@@ -0,0 +1,195 @@
+use crate::config::XmlConfig;
+use crate::rejection::XmlRejection;
+use axum_core::body::Body;
+use axum_core::extract::{FromRequest, Request};
+use bytes::Bytes;
+use core::pin::Pin;
+use quick_xml::events::Event;
+use quick_xml::{DeError, Reader};
+use serde::de::DeserializeOwned;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::ops::Range;
+
+/// XML Extractor for the child elements of the root element
+///
+/// Checks the body the same way as [`Xml`](crate::Xml) and then deserializes
+/// each child of the root element as the iterator is advanced, instead of
+/// collecting them into a `Vec`. The body is still read into memory but only
+/// the current item is deserialized at a time, so large collections can be
+/// processed incrementally. Text content of the root element is ignored.
+///
+/// Iteration stops after the first error. Options that rewrite the whole
+/// document such as [`XmlConfig::repair_malformed`] and
+/// [`XmlConfig::wrap_fragments`] don't apply.
+///
+/// ```rust,no_run
+/// use axum_xml_up::{XmlItems, XmlRejection};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Item {
+///     #[serde(rename = "@id")]
+///     id: u32,
+/// }
+///
+/// async fn import(items: XmlItems<Item>) -> Result<String, XmlRejection> {
+///     // <items><item id="1"/><item id="2"/></items>
+///     let mut total = 0;
+///     for item in items {
+///         total += item?.id;
+///     }
+///     Ok(total.to_string())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct XmlItems<T> {
+    /// The request body
+    bytes: Bytes,
+    /// Offset in the body of the next child element
+    offset: usize,
+    /// Whether the start of the root element has been read
+    in_root: bool,
+    /// Whether all the items have been read or an error occurred
+    finished: bool,
+    /// Config used to deserialize the items
+    config: XmlConfig,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> XmlItems<T> {
+    /// Creates an iterator over the child elements of the document in `bytes`
+    /// that has already been checked against the `config`
+    fn new(bytes: Bytes, mut config: XmlConfig) -> Self {
+        // Each item is a single element so never needs to be wrapped
+        config.wrap_fragments = false;
+
+        Self {
+            bytes,
+            offset: 0,
+            in_root: false,
+            finished: false,
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Finds the range of the next child element of the root element, `None`
+    /// once the end of the root element is reached
+    fn next_range(&mut self) -> Result<Option<Range<usize>>, XmlRejection> {
+        let base = self.offset;
+        let mut reader = Reader::from_reader(&self.bytes[base..]);
+        // The reader starts part way through the root element so can't match
+        // its end tag, the items themselves are checked when deserialized
+        reader.check_end_names(false);
+
+        // Nesting depth within the current item
+        let mut depth: usize = 0;
+        // Offset of the start of the current item
+        let mut start = 0;
+
+        loop {
+            let position = reader.buffer_position();
+            let event = reader.read_event().map_err(DeError::from)?;
+
+            let end = match event {
+                Event::Start(_) if !self.in_root => {
+                    self.in_root = true;
+                    continue;
+                }
+                // The root element doesn't have any children
+                Event::Empty(_) if !self.in_root => return Ok(None),
+                Event::Start(_) => {
+                    if depth == 0 {
+                        start = position;
+                    }
+                    depth += 1;
+                    continue;
+                }
+                Event::Empty(_) if depth == 0 => {
+                    start = position;
+                    reader.buffer_position()
+                }
+                Event::End(_) if depth == 0 => return Ok(None),
+                Event::End(_) => {
+                    depth -= 1;
+                    if depth > 0 {
+                        continue;
+                    }
+                    reader.buffer_position()
+                }
+                Event::Eof if self.in_root => return Err(DeError::UnexpectedEof.into()),
+                Event::Eof => return Ok(None),
+                _ => continue,
+            };
+
+            self.offset = base + end;
+            return Ok(Some(base + start..base + end));
+        }
+    }
+}
+
+impl<T> Iterator for XmlItems<T>
+where
+    T: DeserializeOwned,
+{
+    type Item = Result<T, XmlRejection>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let result = self.next_range().and_then(|range| match range {
+            Some(range) => crate::de::from_bytes(&self.bytes[range], &self.config)
+                .map(Some)
+                .map_err(XmlRejection::from),
+            None => Ok(None),
+        });
+
+        match result {
+            Ok(Some(item)) => Some(Ok(item)),
+            Ok(None) => {
+                self.finished = true;
+                None
+            }
+            Err(err) => {
+                self.finished = true;
+                Some(self.config.report(Err(err)))
+            }
+        }
+    }
+}
+
+impl<T, S> FromRequest<S> for XmlItems<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync + 'static,
+{
+    type Rejection = XmlRejection;
+
+    fn from_request<'state, 'future>(
+        mut req: Request<Body>,
+        state: &'state S,
+    ) -> Pin<Box<dyn Future<Output = Result<Self, Self::Rejection>> + Send + 'future>>
+    where
+        'state: 'future,
+        Self: 'future,
+    {
+        Box::pin(async move {
+            let config = XmlConfig::resolve(&mut req, state);
+            let result = async {
+                let mut bytes = crate::read_body(req, state, &config).await?;
+
+                let len = crate::strip_byte_order_mark(&bytes, &config)?.len();
+                bytes = bytes.split_off(bytes.len() - len);
+
+                crate::scan::scan(&bytes, &config)?;
+                Ok(bytes)
+            };
+
+            let bytes = config.report(result.await)?;
+            Ok(Self::new(bytes, config))
+        })
+    }
+}
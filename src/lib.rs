@@ -6,21 +6,208 @@
 //!
 //! ## Features
 //!
-//! - `encoding`: support non utf-8 payload
+//! - `client`: support serializing request bodies for the extractor, see
+//!   [`Xml::into_request`]
+//! - `compression`: support gzip compressed responses, see [`XmlResponse::compressed`]
+//! - `encoding`: support non utf-8 payload, the body is transcoded using the
+//!   `charset` of the content type or the `encoding` of the XML declaration
+//! - `form`: support for the [`XmlOrForm`] extractor accepting form encoded bodies
+//! - `hexdump`: support including a hexdump of the body around syntax errors in the
+//!   rejection, see [`XmlConfig::hexdump_errors`]
+//! - `json`: support for the [`Negotiate`] extractor and [`XmlOrJson`] response
+//!   choosing between XML and JSON from the `Accept` header, and JSON rejection
+//!   bodies with [`RejectionFormat::Json`]
+//! - `overlapped-lists`: support list items that are not next to each other, see
+//!   [`XmlConfig::event_buffer_size`]
+//! - `stream`: support for the [`StreamingXml`] extractor deserializing the body
+//!   while it is received, of the limits only [`XmlConfig::max_body_size`] and
+//!   [`XmlConfig::max_depth`] apply to it
+//! - `tracing`: emit `trace` level diagnostics about extracted request bodies
+//!
+//! ## Enums chosen by child element
+//!
+//! Documents where the shape depends on which child element is present, such as
+//! `<response><success>..</success></response>` or `<response><error>..</error></response>`,
+//! can be deserialized by capturing the child with the special `$value` name. The
+//! variant is then selected using the element name:
+//!
+//! ```
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct Response {
+//!     #[serde(rename = "$value")]
+//!     result: ResponseResult,
+//! }
+//!
+//! #[derive(Deserialize)]
+//! #[serde(rename_all = "lowercase")]
+//! enum ResponseResult {
+//!     Success { id: u32 },
+//!     Error { message: String },
+//! }
+//! ```
+//!
+//! ## Enums chosen by attribute
+//!
+//! Elements such as `<status code="active"/>` can select the variant using an
+//! attribute by internally tagging the enum with the `@` prefixed attribute name,
+//! other attributes of the element map to the fields of the variant:
+//!
+//! ```
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! #[serde(tag = "@code", rename_all = "lowercase")]
+//! enum Status {
+//!     Active,
+//!     Suspended {
+//!         #[serde(rename = "@until")]
+//!         until: String,
+//!     },
+//! }
+//! ```
+//!
+//! ## Attribute or element values
+//!
+//! Upstreams that send the same value either as an attribute (`<x foo="1"/>`) or as a
+//! child element (`<x><foo>1</foo></x>`) can be accepted from both locations by naming
+//! the field after the attribute and adding the element name as an alias:
+//!
+//! ```
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct X {
+//!     #[serde(rename = "@foo", alias = "foo")]
+//!     foo: u32,
+//! }
+//! ```
+//!
+//! ## One or many elements
+//!
+//! Extracting a `Vec<T>` collects every top level element of the body into the
+//! list, so the same handler accepts either a single `<Item/>` or a sequence of
+//! `<Item/><Item/>` elements:
+//!
+//! ```rust,no_run
+//! use axum_xml_up::Xml;
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct Item {
+//!     #[serde(rename = "@id")]
+//!     id: u32,
+//! }
+//!
+//! async fn create_items(Xml(items): Xml<Vec<Item>>) {
+//!     // items contains one entry for each top level element
+//! }
+//! ```
+//!
+//! Newtypes around a collection such as `struct Items(Vec<Item>)` behave the same
+//! way. When the elements are wrapped in a parent element (`<items><Item/></items>`)
+//! use a struct collecting its children with the special `$value` name:
+//!
+//! ```
+//! # use serde::Deserialize;
+//! # #[derive(Deserialize)]
+//! # struct Item {}
+//! #[derive(Deserialize)]
+//! struct Items {
+//!     #[serde(rename = "$value")]
+//!     items: Vec<Item>,
+//! }
+//! ```
+//!
+//! ## Dynamic values
+//!
+//! Middleware that needs to inspect arbitrary documents can extract a self-describing
+//! value such as [`serde_value::Value`](https://docs.rs/serde-value) without a schema.
+//! Elements become maps keyed by attribute (`@` prefixed) and child element names,
+//! with text content stored under the `$text` key and all values read as strings:
+//!
+//! ```rust,no_run
+//! use axum_xml_up::Xml;
+//! use serde_value::Value;
+//!
+//! async fn inspect(Xml(value): Xml<Value>) {
+//!     // <user id="1"><name>Jacob</name></user> is read as
+//!     // {"@id": "1", "name": {"$text": "Jacob"}}
+//! }
+//! ```
+//!
+//! For a tree that keeps attributes, elements and text order apart use [`XmlValue`].
 
-use crate::rejection::XmlRejection;
 use axum_core::body::Body;
 use axum_core::extract::{FromRequest, Request};
 use axum_core::response::{IntoResponse, Response};
 use bytes::Bytes;
 use core::pin::Pin;
-use http::{header, HeaderValue, StatusCode};
+use http::{header, HeaderName};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::future::Future;
 use std::ops::{Deref, DerefMut};
 
+mod accept;
+#[cfg(feature = "client")]
+mod client;
+#[cfg(feature = "compression")]
+mod compression;
+mod config;
+mod date;
+mod de;
+#[cfg(feature = "encoding")]
+mod encoding;
+mod events;
+mod extract;
+pub mod hex_binary;
+#[cfg(feature = "hexdump")]
+mod hexdump;
+mod items;
+mod limit;
+#[cfg(feature = "json")]
+mod negotiate;
+mod owned;
+mod position;
+mod range;
 mod rejection;
+mod repair;
+mod response;
+pub mod result;
+mod scan;
+pub mod separated;
+mod soap;
+mod space;
+#[cfg(feature = "stream")]
+mod stream;
+mod value;
+pub mod xsi_type;
+
+pub use accept::AcceptXml;
+#[cfg(feature = "compression")]
+pub use compression::AcceptEncoding;
+pub use config::XmlConfig;
+pub use events::XmlEvents;
+#[cfg(feature = "form")]
+pub use extract::XmlOrForm;
+pub use extract::{
+    OptionalXml, PartialXml, RawXml, TimedXml, XmlMeta, XmlWarning, XmlWithAttributes,
+    XmlWithHeaders, XmlWithMeta,
+};
+pub use items::XmlItems;
+#[cfg(feature = "json")]
+pub use negotiate::{Negotiate, XmlOrJson};
+pub use owned::IntoOwned;
+pub use range::ByteRange;
+pub use rejection::{RejectionFormat, XmlErrorKind, XmlLimit, XmlRejected, XmlRejection};
+pub use response::{AsXmlResponse, XmlResponse};
+pub use soap::Soap;
+pub use space::PreserveSpace;
+#[cfg(feature = "stream")]
+pub use stream::StreamingXml;
+pub use value::{XmlNode, XmlValue};
 
 /// XML Extractor / Response.
 ///
@@ -29,6 +216,10 @@ mod rejection;
 /// the `Content-Type: application/xml` header, it will reject the request and return a
 /// `400 Bad Request` response.
 ///
+/// Middleware that has already buffered the body can store it as [`Bytes`] in the request
+/// extensions, the extractor then deserializes the stored body instead of reading the request
+/// body again.
+///
 /// # Extractor example
 ///
 /// ```rust,no_run
@@ -57,8 +248,42 @@ mod rejection;
 /// # };
 /// ```
 ///
+/// Extracting `Result<Xml<T>, XmlRejection>` never rejects the request and instead hands the
+/// [`XmlRejection`] to the handler, so it can be logged or mapped to a domain error:
+///
+/// ```rust,no_run
+/// use axum::http::StatusCode;
+/// use axum_xml_up::{Xml, XmlRejection};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct CreateUser {
+///     email: String,
+/// }
+///
+/// async fn create_user(payload: Result<Xml<CreateUser>, XmlRejection>) -> StatusCode {
+///     match payload {
+///         Ok(Xml(payload)) => StatusCode::CREATED,
+///         Err(rejection) => StatusCode::UNPROCESSABLE_ENTITY,
+///     }
+/// }
+/// ```
+///
+/// Element and attribute names are matched against the fields of a struct without being
+/// allocated, so documents with many repeated names don't benefit from interning the names.
+/// Deserializing into a map such as `HashMap<String, _>` allocates a key for every name, prefer
+/// structs for name heavy documents (compare the `names` group of the `allocations` benchmark).
+///
 /// When used as a response, it can serialize any type that implements [`serde::Serialize`] to
-/// `XML`, and will automatically set `Content-Type: application/xml` header.
+/// `XML`, and will automatically set `Content-Type: application/xml` header. Optional values
+/// serialize as the inner value when present and as an empty body for `None`, see
+/// [`XmlResponse::empty_as`] for emitting an empty root element instead. Values without an
+/// element name such as numbers and strings are wrapped in a `<value>` root element, see
+/// [`XmlResponse::primitive_root`].
+///
+/// Struct fields are written in declaration order, with attribute (`@`) fields always written on
+/// the start tag. Maps are written in their iteration order so a `BTreeMap` should be used where
+/// the output needs to be stable, such as for golden-file tests.
 ///
 /// # Response example
 ///
@@ -99,12 +324,12 @@ pub struct Xml<T>(pub T);
 impl<T, S> FromRequest<S> for Xml<T>
 where
     T: DeserializeOwned,
-    S: Send + Sync,
+    S: Send + Sync + 'static,
 {
     type Rejection = XmlRejection;
 
     fn from_request<'state, 'future>(
-        req: Request<Body>,
+        mut req: Request<Body>,
         state: &'state S,
     ) -> Pin<Box<dyn Future<Output = Result<Self, Self::Rejection>> + Send + 'future>>
     where
@@ -112,35 +337,275 @@ where
         Self: 'future,
     {
         Box::pin(async move {
-            let content_type = content_type(&req);
-            if !content_type.is_some_and(is_xml_type) {
-                return Err(XmlRejection::MissingXMLContentType);
-            }
+            let config = XmlConfig::resolve(&mut req, state);
+            let result = async {
+                let bytes = read_body(req, state, &config).await?;
+                parse(&bytes, &config)
+            };
 
-            let bytes = Bytes::from_request(req, state).await?;
+            config.report(result.await).map(Self)
+        })
+    }
+}
 
-            println!("{:?}", bytes);
+impl<T> Xml<T>
+where
+    T: DeserializeOwned,
+{
+    /// Deserializes `bytes` the same way as the extractor using the default
+    /// [`XmlConfig`], for bodies received outside of axum such as from a
+    /// message queue
+    ///
+    /// ```
+    /// use axum_xml_up::Xml;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Message {
+    ///     #[serde(rename = "@id")]
+    ///     id: u32,
+    /// }
+    ///
+    /// let Xml(message) = Xml::<Message>::from_bytes(br#"<Message id="1"/>"#).unwrap();
+    /// assert_eq!(message.id, 1);
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, XmlRejection> {
+        Self::from_bytes_with_config(bytes, &XmlConfig::default())
+    }
 
-            let value = quick_xml::de::from_reader(&*bytes)?;
+    /// Deserializes `bytes` the same way as the extractor using the provided
+    /// `config`. The checks on the request, such as the content type and
+    /// [`XmlConfig::max_body_size`], don't apply. With the `encoding` feature
+    /// the encoding is read from the XML declaration.
+    pub fn from_bytes_with_config(bytes: &[u8], config: &XmlConfig) -> Result<Self, XmlRejection> {
+        #[cfg(feature = "encoding")]
+        let result = encoding::decode(bytes, None).and_then(|bytes| parse(&bytes, config));
+        #[cfg(not(feature = "encoding"))]
+        let result = parse(bytes, config);
 
-            Ok(Self(value))
-        })
+        config.report(result).map(Self)
     }
 }
 
-/// Obtains and parses the mime type of the Content-Type header
-fn content_type(req: &Request) -> Option<mime::Mime> {
+/// Checks the content type of `req` and reads its body using the `config`. A
+/// body already read by a middleware and stored as [`Bytes`] in the request
+/// extensions is used instead of the request body.
+///
+/// With the `encoding` feature the body is transcoded into UTF-8.
+pub(crate) async fn read_body<S>(
+    req: Request<Body>,
+    state: &S,
+    config: &XmlConfig,
+) -> Result<Bytes, XmlRejection>
+where
+    S: Send + Sync + 'static,
+{
+    check_content_type(&req, config)?;
+    read_body_unchecked(req, state, config).await
+}
+
+/// Checks that `req` has an accepted content type, unless the `config` doesn't
+/// require one
+pub(crate) fn check_content_type(req: &Request, config: &XmlConfig) -> Result<(), XmlRejection> {
+    if !config.require_content_type {
+        return Ok(());
+    }
+
+    let content_type = content_type(req, &config.content_type_header);
+    if !content_type.is_some_and(|mime| {
+        let accepted = match &config.content_type_matcher {
+            Some(matcher) => matcher.matches(&mime),
+            None => is_xml_type(&mime),
+        };
+        accepted || (config.accept_wildcard_type && is_wildcard_type(&mime))
+    }) {
+        return Err(XmlRejection::MissingXMLContentType);
+    }
+
+    Ok(())
+}
+
+/// Reads the body of `req` like [`read_body`] without checking its content type
+pub(crate) async fn read_body_unchecked<S>(
+    req: Request<Body>,
+    state: &S,
+    config: &XmlConfig,
+) -> Result<Bytes, XmlRejection>
+where
+    S: Send + Sync + 'static,
+{
+    // The digest header is captured before the request is consumed
+    let digest = config
+        .body_digest
+        .as_ref()
+        .map(|digest| (digest, req.headers().get(&digest.header).cloned()));
+
+    // The charset is captured before the request is consumed
+    #[cfg(feature = "encoding")]
+    let charset = content_type(&req, &config.content_type_header)
+        .and_then(|mime| mime.get_param(mime::CHARSET).map(|value| value.to_string()));
+
+    let bytes = read_bytes(req, state, config).await?;
+
+    if let Some((digest, expected)) = digest {
+        digest.verify(expected.as_ref(), &bytes)?;
+    }
+
+    if config.blank_body_as_empty && is_blank(&bytes) {
+        return Ok(Bytes::new());
+    }
+
+    #[cfg(feature = "encoding")]
+    let bytes = match encoding::decode(&bytes, charset.as_deref())? {
+        std::borrow::Cow::Borrowed(_) => bytes,
+        std::borrow::Cow::Owned(decoded) => Bytes::from(decoded),
+    };
+
+    Ok(bytes)
+}
+
+/// Reads the body of `req` without decoding it
+async fn read_bytes<S>(
+    mut req: Request<Body>,
+    state: &S,
+    config: &XmlConfig,
+) -> Result<Bytes, XmlRejection>
+where
+    S: Send + Sync + 'static,
+{
+    // Reuse the body when it was already read by a middleware
+    if let Some(bytes) = req.extensions_mut().remove::<Bytes>() {
+        if let Some(limit) = config.max_body_size.filter(|limit| bytes.len() > *limit) {
+            return Err(XmlRejection::BodyTooLarge {
+                limit,
+                size: Some(bytes.len()),
+            });
+        }
+
+        return Ok(bytes);
+    }
+
+    if let Some(limit) = config.max_body_size {
+        // Reject bodies that are known to be too large before reading them
+        let size = content_length(&req);
+        if size.is_some_and(|size| size > limit) {
+            return Err(XmlRejection::BodyTooLarge { limit, size });
+        }
+
+        req = limit::limit_body(req, limit);
+    }
+
+    let bytes =
+        Bytes::from_request(req, state)
+            .await
+            .map_err(|err| match config.max_body_size {
+                Some(limit) if limit::exceeded_limit(&err) => {
+                    XmlRejection::BodyTooLarge { limit, size: None }
+                }
+                _ if config.normalize_body_errors => XmlRejection::BodyReadFailed(err),
+                _ => XmlRejection::BytesRejection(err),
+            })?;
+
+    #[cfg(feature = "tracing")]
+    tracing::trace!(len = bytes.len(), "read XML request body");
+
+    Ok(bytes)
+}
+
+/// Checks the document in `bytes` against the `config` and deserializes it
+pub(crate) fn parse<T>(bytes: &[u8], config: &XmlConfig) -> Result<T, XmlRejection>
+where
+    T: DeserializeOwned,
+{
+    let bytes = strip_byte_order_mark(bytes, config)?;
+
+    let repaired;
+    let bytes = if config.repair_malformed {
+        repaired = repair::repair(bytes);
+        repaired.as_ref()
+    } else {
+        bytes
+    };
+
+    let result = match scan::scan(bytes, config) {
+        // Container level defaults only apply when the root element exists
+        Err(XmlRejection::NoRootElement) if config.missing_root_as_default => {
+            return de::from_bytes(EMPTY_ROOT, config).map_err(XmlRejection::from);
+        }
+        result => result.and_then(|_| de::from_bytes(bytes, config).map_err(XmlRejection::from)),
+    };
+
+    match result {
+        Err(XmlRejection::InvalidXMLBody(err)) => Err(position::annotate(bytes, err, config)),
+        result => result,
+    }
+}
+
+/// Document deserialized by [`XmlConfig::missing_root_as_default`]
+const EMPTY_ROOT: &[u8] = b"<root/>";
+
+/// Checks that `bytes` is a well-formed document matching the restrictions in
+/// the `config` without deserializing it
+pub(crate) fn validate(bytes: &[u8], config: &XmlConfig) -> Result<(), XmlRejection> {
+    let checked = strip_byte_order_mark(bytes, config)?;
+
+    let result = scan::scan(checked, config)
+        .and_then(|_| scan::well_formed(checked).map_err(XmlRejection::from));
+
+    match result {
+        Err(XmlRejection::InvalidXMLBody(err)) => Err(position::annotate(checked, err, config)),
+        result => result,
+    }
+}
+
+/// UTF-8 byte order mark stripped from the start of bodies
+const BYTE_ORDER_MARK: &[u8] = b"\xEF\xBB\xBF";
+
+/// Strips the byte order mark from the start of `bytes`, unless the `config`
+/// rejects bodies starting with one
+pub(crate) fn strip_byte_order_mark<'a>(
+    bytes: &'a [u8],
+    config: &XmlConfig,
+) -> Result<&'a [u8], XmlRejection> {
+    match bytes.strip_prefix(BYTE_ORDER_MARK) {
+        Some(_) if config.reject_byte_order_mark => Err(XmlRejection::ByteOrderMark),
+        Some(bytes) => Ok(bytes),
+        None => Ok(bytes),
+    }
+}
+
+/// Checks whether `bytes` only contains a byte order mark and whitespace
+fn is_blank(bytes: &[u8]) -> bool {
+    bytes
+        .strip_prefix(BYTE_ORDER_MARK)
+        .unwrap_or(bytes)
+        .iter()
+        .all(u8::is_ascii_whitespace)
+}
+
+/// Obtains and parses the mime type of the content type header `name`
+fn content_type(req: &Request, name: &HeaderName) -> Option<mime::Mime> {
     req.headers()
         // Get content type header
-        .get(header::CONTENT_TYPE)
+        .get(name)
         // Get the header string value
         .and_then(|value| value.to_str().ok())
+        // Trim stray whitespace some clients include
+        .map(str::trim)
         // Parse the mime type
         .and_then(|value| value.parse::<mime::Mime>().ok())
 }
 
+/// Obtains the length of the body from the Content-Length header
+pub(crate) fn content_length(req: &Request) -> Option<usize> {
+    req.headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+}
+
 /// Checks whether the provided mime type can be considered xml
-fn is_xml_type(mime: mime::Mime) -> bool {
+fn is_xml_type(mime: &mime::Mime) -> bool {
     let type_ = mime.type_();
     // Ensure the main type is application/ or text/
     (type_ == "application" || type_ == "text")
@@ -148,6 +613,11 @@ fn is_xml_type(mime: mime::Mime) -> bool {
         && (mime.subtype() == "xml" || mime.suffix().is_some_and(|value| value == "xml"))
 }
 
+/// Checks whether the provided mime type is the `application/*` wildcard
+fn is_wildcard_type(mime: &mime::Mime) -> bool {
+    mime.type_() == mime::APPLICATION && mime.subtype() == mime::STAR
+}
+
 impl<T> Deref for Xml<T> {
     type Target = T;
 
@@ -173,24 +643,6 @@ where
     T: Serialize,
 {
     fn into_response(self) -> Response {
-        match quick_xml::se::to_string(&self.0) {
-            Ok(value) => (
-                [(
-                    header::CONTENT_TYPE,
-                    HeaderValue::from_static("application/xml"),
-                )],
-                value,
-            )
-                .into_response(),
-            Err(err) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                [(
-                    header::CONTENT_TYPE,
-                    HeaderValue::from_static(mime::TEXT_PLAIN_UTF_8.as_ref()),
-                )],
-                err.to_string(),
-            )
-                .into_response(),
-        }
+        XmlResponse::from(self).into_response()
     }
 }
@@ -0,0 +1,46 @@
+use axum_core::body::Body;
+use axum_core::extract::Request;
+use http_body_util::{BodyExt, LengthLimitError, Limited};
+use std::error::Error;
+use std::fmt;
+
+/// Error reading a body larger than [`XmlConfig::max_body_size`], kept apart
+/// from the [`LengthLimitError`] of axum's own `DefaultBodyLimit`
+///
+/// [`XmlConfig::max_body_size`]: crate::XmlConfig::max_body_size
+#[derive(Debug)]
+struct BodyLimitExceeded;
+
+impl fmt::Display for BodyLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("body exceeded the configured limit")
+    }
+}
+
+impl Error for BodyLimitExceeded {}
+
+/// Limits the body of `req` to `limit` bytes while it is read
+pub(crate) fn limit_body(req: Request, limit: usize) -> Request {
+    req.map(|body| {
+        Body::new(Limited::new(body, limit).map_err(|err| {
+            if err.is::<LengthLimitError>() {
+                Box::new(BodyLimitExceeded)
+            } else {
+                err
+            }
+        }))
+    })
+}
+
+/// Checks whether reading the body failed because of the limit applied using
+/// [`limit_body`]
+pub(crate) fn exceeded_limit(err: &(dyn Error + 'static)) -> bool {
+    let mut source = Some(err);
+    while let Some(err) = source {
+        if err.is::<BodyLimitExceeded>() {
+            return true;
+        }
+        source = err.source();
+    }
+    false
+}
@@ -0,0 +1,145 @@
+use crate::accept::{specificity, Preference};
+use crate::Xml;
+use axum_core::extract::FromRequestParts;
+use axum_core::response::{IntoResponse, Response};
+use core::pin::Pin;
+use http::request::Parts;
+use http::{header, HeaderMap, HeaderValue, StatusCode};
+use serde::Serialize;
+use std::convert::Infallible;
+use std::future::Future;
+
+/// Response format preferred by the client, chosen from the `Accept` request
+/// header
+///
+/// The media range with the highest quality value that covers XML or JSON
+/// decides the format, more specific ranges are preferred over wildcards with
+/// the same quality and XML is used when both are equally acceptable, when
+/// neither is acceptable or when the client didn't send an `Accept` header.
+///
+/// ```rust,no_run
+/// use axum_xml_up::{Negotiate, XmlOrJson};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     id: u32,
+/// }
+///
+/// async fn get_user(negotiate: Negotiate) -> XmlOrJson<User> {
+///     negotiate.respond(User { id: 1 })
+/// }
+/// ```
+///
+/// Requires the `json` feature.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Negotiate {
+    /// `application/xml` serialized using quick-xml
+    #[default]
+    Xml,
+    /// `application/json` serialized using serde_json
+    Json,
+}
+
+impl Negotiate {
+    /// Chooses the preferred format from the `Accept` headers
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let mut xml = Preference::default();
+        let mut json = Preference::default();
+
+        for (index, (mime, quality)) in crate::accept::media_ranges(headers).enumerate() {
+            let specificity = specificity(&mime);
+            if crate::accept::accepts_xml(&mime) {
+                xml.update(quality, specificity, index);
+            }
+            if specificity == 0 || accepts_json(&mime) {
+                json.update(quality, specificity, index);
+            }
+        }
+
+        if json.is_preferred_to(&xml) {
+            Self::Json
+        } else {
+            Self::Xml
+        }
+    }
+
+    /// Creates a response serializing `value` in this format
+    pub fn respond<T>(self, value: T) -> XmlOrJson<T> {
+        XmlOrJson(self, value)
+    }
+}
+
+impl<S> FromRequestParts<S> for Negotiate
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    fn from_request_parts<'parts, 'state, 'future>(
+        parts: &'parts mut Parts,
+        _state: &'state S,
+    ) -> Pin<Box<dyn Future<Output = Result<Self, Self::Rejection>> + Send + 'future>>
+    where
+        'parts: 'future,
+        'state: 'future,
+        Self: 'future,
+    {
+        Box::pin(async move { Ok(Self::from_headers(&parts.headers)) })
+    }
+}
+
+/// Checks whether the media range `mime` includes a JSON type, wildcards
+/// covering all types are checked separately
+fn accepts_json(mime: &mime::Mime) -> bool {
+    let suffix = mime.suffix().map(|suffix| suffix.as_str());
+    mime.type_() == mime::APPLICATION
+        && (mime.subtype() == mime::JSON || mime.subtype() == mime::STAR || suffix == Some("json"))
+}
+
+/// XML or JSON response, serialized in the format chosen by [`Negotiate`]
+///
+/// XML responses are serialized the same way as [`Xml`], JSON responses use
+/// serde_json with the `application/json` content type. Both formats include
+/// a `Vary: Accept` header.
+///
+/// Requires the `json` feature.
+#[derive(Debug, Clone, Copy)]
+pub struct XmlOrJson<T>(pub Negotiate, pub T);
+
+impl<T> IntoResponse for XmlOrJson<T>
+where
+    T: Serialize,
+{
+    fn into_response(self) -> Response {
+        let XmlOrJson(format, value) = self;
+        let mut response = match format {
+            Negotiate::Xml => Xml(value).into_response(),
+            Negotiate::Json => match serde_json::to_vec(&value) {
+                Ok(body) => (
+                    [(
+                        header::CONTENT_TYPE,
+                        HeaderValue::from_static(mime::APPLICATION_JSON.as_ref()),
+                    )],
+                    body,
+                )
+                    .into_response(),
+                Err(err) => (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    [(
+                        header::CONTENT_TYPE,
+                        HeaderValue::from_static(mime::TEXT_PLAIN_UTF_8.as_ref()),
+                    )],
+                    err.to_string(),
+                )
+                    .into_response(),
+            },
+        };
+
+        response
+            .headers_mut()
+            .append(header::VARY, HeaderValue::from_static("accept"));
+        response
+    }
+}
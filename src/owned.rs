@@ -0,0 +1,61 @@
+use crate::Xml;
+use std::borrow::Cow;
+
+/// Conversion of a value that borrows data into a version that owns it
+///
+/// The request body is not kept around after extraction so types that are
+/// generic over a lifetime can be extracted directly as `Xml<T<'static>>` as
+/// long as they don't use `#[serde(borrow)]` (serde deserializes `Cow` fields
+/// as owned by default). Values deserialized while borrowing from some other
+/// buffer can be turned into the owned form using [`Xml::into_owned`].
+///
+/// ```
+/// use axum_xml_up::{IntoOwned, Xml};
+/// use serde::Deserialize;
+/// use std::borrow::Cow;
+///
+/// #[derive(Deserialize)]
+/// struct Message<'a> {
+///     #[serde(borrow)]
+///     text: Cow<'a, str>,
+/// }
+///
+/// impl IntoOwned for Message<'_> {
+///     type Owned = Message<'static>;
+///
+///     fn into_owned(self) -> Self::Owned {
+///         Message {
+///             // `Cow::into_owned` would produce a `String`
+///             text: IntoOwned::into_owned(self.text),
+///         }
+///     }
+/// }
+/// ```
+pub trait IntoOwned {
+    /// The owned version of the type
+    type Owned: 'static;
+
+    /// Converts the value into its owned version
+    fn into_owned(self) -> Self::Owned;
+}
+
+impl<B> IntoOwned for Cow<'_, B>
+where
+    B: ToOwned + ?Sized + 'static,
+{
+    type Owned = Cow<'static, B>;
+
+    fn into_owned(self) -> Cow<'static, B> {
+        Cow::Owned(Cow::into_owned(self))
+    }
+}
+
+impl<T> Xml<T>
+where
+    T: IntoOwned,
+{
+    /// Converts the inner value into its owned version
+    pub fn into_owned(self) -> Xml<T::Owned> {
+        Xml(self.0.into_owned())
+    }
+}
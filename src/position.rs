@@ -0,0 +1,61 @@
+use crate::config::XmlConfig;
+use crate::rejection::XmlRejection;
+use quick_xml::events::Event;
+use quick_xml::{DeError, Reader};
+
+/// Adds the position of the first syntax error in `bytes` to `error`, along
+/// with a hexdump of the surrounding bytes when enabled by the `config`.
+/// Errors that don't come from a syntax error in the document, such as a
+/// missing field, are returned unchanged.
+pub(crate) fn annotate(bytes: &[u8], error: DeError, config: &XmlConfig) -> XmlRejection {
+    let Some(offset) = error_offset(bytes) else {
+        return XmlRejection::InvalidXMLBody(error);
+    };
+
+    let (line, column) = line_column(bytes, offset);
+
+    #[cfg(feature = "hexdump")]
+    let hexdump = config
+        .hexdump_errors
+        .then(|| crate::hexdump::hexdump(bytes, offset));
+    #[cfg(not(feature = "hexdump"))]
+    let hexdump = {
+        let _ = config;
+        None
+    };
+
+    XmlRejection::InvalidXMLBodyAt {
+        error,
+        offset,
+        line,
+        column,
+        hexdump,
+    }
+}
+
+/// Finds the offset of the first syntax error in `bytes`
+fn error_offset(bytes: &[u8]) -> Option<usize> {
+    let mut reader = Reader::from_reader(bytes);
+    // Unbalanced end tags are only reported when the names are checked
+    reader.check_end_names(true);
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Eof) => return None,
+            Ok(_) => {}
+            Err(_) => return Some(reader.buffer_position().min(bytes.len())),
+        }
+    }
+}
+
+/// Determines the line and column of `offset` in `bytes`, both starting from `1`
+fn line_column(bytes: &[u8], offset: usize) -> (usize, usize) {
+    let before = &bytes[..offset];
+    let line = before.iter().filter(|&&byte| byte == b'\n').count() + 1;
+    let line_start = before
+        .iter()
+        .rposition(|&byte| byte == b'\n')
+        .map_or(0, |index| index + 1);
+
+    (line, offset - line_start + 1)
+}
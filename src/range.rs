@@ -0,0 +1,150 @@
+use axum_core::extract::FromRequestParts;
+use axum_core::response::{IntoResponse, Response};
+use bytes::Bytes;
+use core::pin::Pin;
+use http::request::Parts;
+use http::{header, HeaderMap, StatusCode};
+use std::convert::Infallible;
+use std::future::Future;
+use std::ops::Range;
+
+/// Extractor for the byte range the client requested using the `Range` header
+///
+/// Used with [`XmlResponse::range`] to respond with only the requested part of
+/// a large document, allowing clients to resume interrupted downloads.
+///
+/// Only a single range is supported, requests for multiple ranges or with an
+/// invalid `Range` header receive the full body.
+///
+/// ```rust,no_run
+/// use axum_xml_up::{ByteRange, Xml, XmlResponse};
+///
+/// async fn get_export(range: ByteRange) -> XmlResponse<Vec<u32>> {
+///     Xml(vec![1, 2, 3]).range(range)
+/// }
+/// ```
+///
+/// [`XmlResponse::range`]: crate::XmlResponse::range
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ByteRange {
+    /// The requested range
+    spec: Option<RangeSpec>,
+}
+
+/// Byte range from a `Range` header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RangeSpec {
+    /// Range starting at an offset up to an optional inclusive end offset
+    From(usize, Option<usize>),
+    /// Range of the last bytes of the body
+    Suffix(usize),
+}
+
+impl ByteRange {
+    /// Determines the requested range from the `Range` header
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let spec = headers
+            .get(header::RANGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_range);
+
+        Self { spec }
+    }
+
+    /// Whether the client requested a range
+    pub fn is_requested(&self) -> bool {
+        self.spec.is_some()
+    }
+
+    /// Resolves the range against a body of `len` bytes, `None` when no range
+    /// was requested and `Some(Err(()))` when the range can't be satisfied
+    fn resolve(&self, len: usize) -> Option<Result<Range<usize>, ()>> {
+        let range = match self.spec? {
+            RangeSpec::From(start, _) if start >= len => Err(()),
+            RangeSpec::From(start, end) => {
+                let end = end.map_or(len, |end| end.saturating_add(1).min(len));
+                Ok(start..end)
+            }
+            RangeSpec::Suffix(suffix) if suffix == 0 || len == 0 => Err(()),
+            RangeSpec::Suffix(suffix) => Ok(len.saturating_sub(suffix)..len),
+        };
+
+        Some(range)
+    }
+
+    /// Creates the response for `body` containing only the requested range
+    pub(crate) fn respond(&self, body: Bytes) -> Response {
+        let len = body.len();
+        match self.resolve(len) {
+            None => body.into_response(),
+            Some(Ok(range)) => {
+                let content_range = format!("bytes {}-{}/{len}", range.start, range.end - 1);
+                (
+                    StatusCode::PARTIAL_CONTENT,
+                    [(header::CONTENT_RANGE, content_range)],
+                    body.slice(range),
+                )
+                    .into_response()
+            }
+            Some(Err(())) => (
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                [
+                    (header::CONTENT_RANGE, format!("bytes */{len}")),
+                    (
+                        header::CONTENT_TYPE,
+                        mime::TEXT_PLAIN_UTF_8.as_ref().to_string(),
+                    ),
+                ],
+                "Requested range not satisfiable",
+            )
+                .into_response(),
+        }
+    }
+}
+
+/// Parses a `bytes=start-end` range, ignoring ranges that are invalid or
+/// contain multiple ranges
+fn parse_range(value: &str) -> Option<RangeSpec> {
+    let spec = value.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') {
+        return None;
+    }
+
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = (start.trim(), end.trim());
+
+    if start.is_empty() {
+        return end.parse().ok().map(RangeSpec::Suffix);
+    }
+
+    let start = start.parse().ok()?;
+    let end = match end {
+        "" => None,
+        end => Some(end.parse().ok()?),
+    };
+
+    if end.is_some_and(|end| end < start) {
+        return None;
+    }
+
+    Some(RangeSpec::From(start, end))
+}
+
+impl<S> FromRequestParts<S> for ByteRange
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    fn from_request_parts<'parts, 'state, 'future>(
+        parts: &'parts mut Parts,
+        _state: &'state S,
+    ) -> Pin<Box<dyn Future<Output = Result<Self, Self::Rejection>> + Send + 'future>>
+    where
+        'parts: 'future,
+        'state: 'future,
+        Self: 'future,
+    {
+        Box::pin(async move { Ok(Self::from_headers(&parts.headers)) })
+    }
+}
@@ -1,35 +1,533 @@
 use axum_core::body::Body;
 use axum_core::extract::rejection::BytesRejection;
+use axum_core::extract::FromRequestParts;
 use axum_core::response::Response;
-use http::StatusCode;
+use core::pin::Pin;
+use http::request::Parts;
+use http::{header, HeaderMap, HeaderValue, StatusCode};
+use quick_xml::DeError;
+use serde::Serialize;
+use std::convert::Infallible;
+use std::fmt;
+use std::future::Future;
 use thiserror::Error;
 
 use crate::IntoResponse;
 
+/// Rejection used for [`Xml`](crate::Xml)
+///
+/// Handlers can also return a rejection using `Result<Xml<T>, XmlRejection>`,
+/// the error is rendered the same way as when extraction fails.
 #[derive(Debug, Error)]
 pub enum XmlRejection {
+    /// The request body could not be deserialized
     #[error("Failed to parse the request body as XML")]
-    InvalidXMLBody(#[from] quick_xml::DeError),
+    InvalidXMLBody(#[from] DeError),
+    /// The request body is not well-formed XML, used instead of
+    /// [`XmlRejection::InvalidXMLBody`] for syntax errors whose position in the
+    /// body could be found
+    #[error(
+        "Failed to parse the request body as XML at byte {offset} (line {line}, column {column}){}",
+        hexdump.as_ref().map(|hexdump| format!("\n{hexdump}")).unwrap_or_default()
+    )]
+    InvalidXMLBodyAt {
+        /// The deserialization error
+        #[source]
+        error: DeError,
+        /// Offset of the first syntax error in the body
+        offset: usize,
+        /// Line of the first syntax error, starting from `1`
+        line: usize,
+        /// Column of the first syntax error in bytes, starting from `1`
+        column: usize,
+        /// Hexdump of the body around the offset, only present when
+        /// [`XmlConfig::hexdump_errors`] is enabled
+        ///
+        /// [`XmlConfig::hexdump_errors`]: crate::XmlConfig::hexdump_errors
+        hexdump: Option<String>,
+    },
+    /// The request did not have an XML content type
     #[error("Expected request with `Content-Type: application/xml`")]
     MissingXMLContentType,
+    /// The request body did not contain a root element
+    #[error("Expected the request body to contain a root element")]
+    NoRootElement,
+    /// The request body contained text without any elements, such as `just text`
+    #[error("Expected the request body to be an XML document, found text without any elements")]
+    NotADocument,
+    /// The root element of the request body was not one of the names allowed
+    /// by [`XmlConfig::allowed_roots`]
+    ///
+    /// [`XmlConfig::allowed_roots`]: crate::XmlConfig::allowed_roots
+    #[error("Root element `{found}` is not allowed, expected one of: {}", .allowed.join(", "))]
+    RootNotAllowed {
+        /// Name of the root element of the body
+        found: String,
+        /// Names of the allowed root elements
+        allowed: Vec<String>,
+    },
+    /// The request body did not contain one of the paths required by
+    /// [`XmlConfig::required_paths`]
+    ///
+    /// [`XmlConfig::required_paths`]: crate::XmlConfig::required_paths
+    #[error("Missing required element or attribute `{0}`")]
+    MissingRequiredPath(String),
+    /// The request body declared an XML version other than `1.0` while
+    /// [`XmlConfig::require_version_1_0`] is enabled
+    ///
+    /// [`XmlConfig::require_version_1_0`]: crate::XmlConfig::require_version_1_0
+    #[error("Unsupported XML version `{0}`, expected `1.0`")]
+    UnsupportedVersion(String),
+    /// The request body contained a processing instruction (such as
+    /// `<?xml-stylesheet ...?>`) while [`XmlConfig::reject_processing_instructions`]
+    /// is enabled
+    ///
+    /// [`XmlConfig::reject_processing_instructions`]: crate::XmlConfig::reject_processing_instructions
+    #[error("Request body contained a processing instruction")]
+    ProcessingInstruction,
+    /// The request body contained a `<!DOCTYPE ...>` declaration while
+    /// [`XmlConfig::forbid_doctype`] is enabled
+    ///
+    /// [`XmlConfig::forbid_doctype`]: crate::XmlConfig::forbid_doctype
+    #[error("Request body must not contain a DOCTYPE declaration")]
+    ForbiddenDoctype,
+    /// The request body started with a byte order mark while
+    /// [`XmlConfig::reject_byte_order_mark`] is enabled
+    ///
+    /// [`XmlConfig::reject_byte_order_mark`]: crate::XmlConfig::reject_byte_order_mark
+    #[error("Request body must not start with a byte order mark")]
+    ByteOrderMark,
+    /// The request body used a character encoding that is not supported, either
+    /// from the `charset` of the content type or the XML declaration
+    ///
+    /// Requires the `encoding` feature.
+    #[cfg(feature = "encoding")]
+    #[error("Unsupported character encoding `{0}`")]
+    UnsupportedEncoding(String),
+    /// The digest of the request body did not match the header configured by
+    /// [`XmlConfig::body_digest`], or the header was missing
+    ///
+    /// [`XmlConfig::body_digest`]: crate::XmlConfig::body_digest
+    #[error("Request body did not match the digest in the `{0}` header")]
+    DigestMismatch(String),
+    /// The request body exceeded one of the configured document limits
+    #[error("Request body exceeded the {0} limit")]
+    LimitExceeded(XmlLimit),
+    /// The request body was larger than [`XmlConfig::max_body_size`]
+    ///
+    /// [`XmlConfig::max_body_size`]: crate::XmlConfig::max_body_size
+    #[error("Request body exceeded the limit of {limit} bytes{}", observed_size(.size))]
+    BodyTooLarge {
+        /// The configured limit in bytes
+        limit: usize,
+        /// Size of the body in bytes, when known from the `Content-Length` header
+        size: Option<usize>,
+    },
+    /// The request body could not be read
     #[error("{0}")]
     BytesRejection(#[from] BytesRejection),
+    /// The request body could not be read, used instead of
+    /// [`XmlRejection::BytesRejection`] when [`XmlConfig::normalize_body_errors`]
+    /// is enabled
+    ///
+    /// [`XmlConfig::normalize_body_errors`]: crate::XmlConfig::normalize_body_errors
+    #[error("Failed to read the request body")]
+    BodyReadFailed(#[source] BytesRejection),
+    /// The request body could not be deserialized as a form, used by
+    /// [`XmlOrForm`](crate::XmlOrForm)
+    #[cfg(feature = "form")]
+    #[error("Failed to parse the request body as a form")]
+    InvalidFormBody(#[from] serde_urlencoded::de::Error),
+}
+
+/// Formats the observed size of a body for [`XmlRejection::BodyTooLarge`]
+fn observed_size(size: &Option<usize>) -> String {
+    size.map(|size| format!(" (received {size} bytes)"))
+        .unwrap_or_default()
+}
+
+/// Document limit that was exceeded, see [`XmlRejection::LimitExceeded`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum XmlLimit {
+    /// Ratio of elements to text content, see [`XmlConfig::max_markup_ratio`]
+    ///
+    /// [`XmlConfig::max_markup_ratio`]: crate::XmlConfig::max_markup_ratio
+    MarkupRatio,
+    /// Nesting depth of elements, see [`XmlConfig::max_depth`]
+    ///
+    /// [`XmlConfig::max_depth`]: crate::XmlConfig::max_depth
+    Depth,
+    /// Length of a comment, see [`XmlConfig::max_comment_len`]
+    ///
+    /// [`XmlConfig::max_comment_len`]: crate::XmlConfig::max_comment_len
+    CommentLength,
+    /// Number of comments, see [`XmlConfig::max_comments`]
+    ///
+    /// [`XmlConfig::max_comments`]: crate::XmlConfig::max_comments
+    Comments,
+    /// Number of distinct element names, see
+    /// [`XmlConfig::max_unique_element_names`]
+    ///
+    /// [`XmlConfig::max_unique_element_names`]: crate::XmlConfig::max_unique_element_names
+    UniqueNames,
+    /// Number of entity references, see [`XmlConfig::max_entity_expansions`]
+    ///
+    /// [`XmlConfig::max_entity_expansions`]: crate::XmlConfig::max_entity_expansions
+    EntityExpansions,
+}
+
+impl fmt::Display for XmlLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            XmlLimit::MarkupRatio => "markup to text ratio",
+            XmlLimit::Depth => "nesting depth",
+            XmlLimit::CommentLength => "comment length",
+            XmlLimit::Comments => "comment count",
+            XmlLimit::UniqueNames => "unique element name",
+            XmlLimit::EntityExpansions => "entity expansion",
+        })
+    }
+}
+
+/// Classification of the cause of an [`XmlRejection`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum XmlErrorKind {
+    /// The body is not well-formed XML or doesn't have the expected structure
+    Syntax,
+    /// A value in the body could not be converted into the expected type
+    TypeMismatch,
+    /// A required field was not present in the body
+    MissingField,
+    /// The body could not be read
+    Io,
+    /// The request did not have an XML content type
+    ContentType,
+    /// The body exceeded one of the configured document limits
+    Limit,
+    /// Any other deserialization error
+    Other,
+}
+
+impl XmlRejection {
+    /// Classifies the rejection allowing it to be handled programmatically, such
+    /// as choosing whether a request is worth retrying
+    pub fn kind(&self) -> XmlErrorKind {
+        match self {
+            XmlRejection::InvalidXMLBody(err) => de_error_kind(err),
+            XmlRejection::InvalidXMLBodyAt { error, .. } => de_error_kind(error),
+            XmlRejection::MissingXMLContentType => XmlErrorKind::ContentType,
+            XmlRejection::MissingRequiredPath(_) => XmlErrorKind::MissingField,
+            #[cfg(feature = "encoding")]
+            XmlRejection::UnsupportedEncoding(_) => XmlErrorKind::ContentType,
+            XmlRejection::NoRootElement
+            | XmlRejection::NotADocument
+            | XmlRejection::RootNotAllowed { .. }
+            | XmlRejection::UnsupportedVersion(_)
+            | XmlRejection::ProcessingInstruction
+            | XmlRejection::ForbiddenDoctype
+            | XmlRejection::ByteOrderMark => XmlErrorKind::Syntax,
+            XmlRejection::DigestMismatch(_) => XmlErrorKind::Other,
+            XmlRejection::LimitExceeded(_) | XmlRejection::BodyTooLarge { .. } => {
+                XmlErrorKind::Limit
+            }
+            XmlRejection::BytesRejection(_) | XmlRejection::BodyReadFailed(_) => XmlErrorKind::Io,
+            #[cfg(feature = "form")]
+            XmlRejection::InvalidFormBody(err) => message_kind(&err.to_string()),
+        }
+    }
+
+    /// The quick-xml error the body failed to deserialize with, for the
+    /// [`XmlRejection::InvalidXMLBody`] and [`XmlRejection::InvalidXMLBodyAt`]
+    /// variants. The same error is also the [`source`](std::error::Error::source)
+    /// of the rejection.
+    pub fn as_deserialize_error(&self) -> Option<&DeError> {
+        match self {
+            XmlRejection::InvalidXMLBody(err) => Some(err),
+            XmlRejection::InvalidXMLBodyAt { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Classifies a deserialization error
+fn de_error_kind(err: &DeError) -> XmlErrorKind {
+    match err {
+        DeError::InvalidXml(quick_xml::Error::Io(_)) => XmlErrorKind::Io,
+        DeError::InvalidXml(_)
+        | DeError::UnexpectedStart(_)
+        | DeError::UnexpectedEnd(_)
+        | DeError::UnexpectedEof
+        | DeError::ExpectedStart => XmlErrorKind::Syntax,
+        DeError::InvalidInt(_) | DeError::InvalidFloat(_) | DeError::InvalidBoolean(_) => {
+            XmlErrorKind::TypeMismatch
+        }
+        DeError::Custom(message) => message_kind(message),
+        _ => XmlErrorKind::Other,
+    }
+}
+
+/// Classifies an error from a serde derived implementation, these errors are
+/// only available as messages
+fn message_kind(message: &str) -> XmlErrorKind {
+    if message.starts_with("missing field") {
+        XmlErrorKind::MissingField
+    } else if message.starts_with("invalid type")
+        || message.starts_with("invalid value")
+        || message.starts_with("invalid length")
+    {
+        XmlErrorKind::TypeMismatch
+    } else {
+        XmlErrorKind::Other
+    }
+}
+
+/// Marker inserted into the response extensions of rejected requests
+///
+/// Allows middleware such as a `tower_http::trace::TraceLayer` to record XML
+/// failures separately from other client errors by inspecting the response.
+///
+/// ```
+/// use axum::response::IntoResponse;
+/// use axum_xml_up::{XmlErrorKind, XmlRejected, XmlRejection};
+///
+/// let response = XmlRejection::MissingXMLContentType.into_response();
+/// let rejected = response.extensions().get::<XmlRejected>().unwrap();
+/// assert_eq!(rejected.kind, XmlErrorKind::ContentType);
+/// assert_eq!(rejected.variant, "MissingXMLContentType");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct XmlRejected {
+    /// Classification of the rejection
+    pub kind: XmlErrorKind,
+    /// Name of the [`XmlRejection`] variant
+    pub variant: &'static str,
+}
+
+impl XmlRejection {
+    /// Name of the rejection variant
+    pub fn variant_name(&self) -> &'static str {
+        match self {
+            XmlRejection::InvalidXMLBody(_) => "InvalidXMLBody",
+            XmlRejection::InvalidXMLBodyAt { .. } => "InvalidXMLBodyAt",
+            XmlRejection::MissingXMLContentType => "MissingXMLContentType",
+            XmlRejection::NoRootElement => "NoRootElement",
+            XmlRejection::NotADocument => "NotADocument",
+            XmlRejection::RootNotAllowed { .. } => "RootNotAllowed",
+            XmlRejection::MissingRequiredPath(_) => "MissingRequiredPath",
+            XmlRejection::UnsupportedVersion(_) => "UnsupportedVersion",
+            XmlRejection::ProcessingInstruction => "ProcessingInstruction",
+            XmlRejection::ForbiddenDoctype => "ForbiddenDoctype",
+            XmlRejection::ByteOrderMark => "ByteOrderMark",
+            XmlRejection::DigestMismatch(_) => "DigestMismatch",
+            #[cfg(feature = "encoding")]
+            XmlRejection::UnsupportedEncoding(_) => "UnsupportedEncoding",
+            XmlRejection::LimitExceeded(_) => "LimitExceeded",
+            XmlRejection::BodyTooLarge { .. } => "BodyTooLarge",
+            XmlRejection::BytesRejection(_) => "BytesRejection",
+            XmlRejection::BodyReadFailed(_) => "BodyReadFailed",
+            #[cfg(feature = "form")]
+            XmlRejection::InvalidFormBody(_) => "InvalidFormBody",
+        }
+    }
+}
+
+impl XmlRejection {
+    /// Status code of the rejection response
+    fn status(&self) -> StatusCode {
+        match self {
+            #[cfg(feature = "form")]
+            XmlRejection::InvalidFormBody(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            XmlRejection::InvalidXMLBody(_)
+            | XmlRejection::InvalidXMLBodyAt { .. }
+            | XmlRejection::MissingRequiredPath(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            #[cfg(feature = "encoding")]
+            XmlRejection::UnsupportedEncoding(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            XmlRejection::MissingXMLContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            XmlRejection::NoRootElement
+            | XmlRejection::NotADocument
+            | XmlRejection::RootNotAllowed { .. }
+            | XmlRejection::UnsupportedVersion(_)
+            | XmlRejection::ProcessingInstruction
+            | XmlRejection::ForbiddenDoctype
+            | XmlRejection::ByteOrderMark
+            | XmlRejection::DigestMismatch(_)
+            | XmlRejection::LimitExceeded(_) => StatusCode::BAD_REQUEST,
+            XmlRejection::BodyTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            XmlRejection::BodyReadFailed(inner) => inner.status(),
+            XmlRejection::BytesRejection(inner) => inner.status(),
+        }
+    }
 }
 
 impl IntoResponse for XmlRejection {
     fn into_response(self) -> crate::Response {
-        match self {
-            e @ XmlRejection::InvalidXMLBody(_) => {
+        let rejected = XmlRejected {
+            kind: self.kind(),
+            variant: self.variant_name(),
+        };
+
+        let mut res = match self {
+            // Rejections from reading the body keep the response from axum
+            XmlRejection::BytesRejection(e) => e.into_response(),
+            e => {
                 let mut res = Response::new(Body::new(e.to_string()));
-                *res.status_mut() = StatusCode::UNPROCESSABLE_ENTITY;
+                *res.status_mut() = e.status();
+                res.headers_mut().insert(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static(mime::TEXT_PLAIN_UTF_8.as_ref()),
+                );
                 res
             }
-            e @ XmlRejection::MissingXMLContentType => {
-                let mut res = Response::new(Body::new(e.to_string()));
-                *res.status_mut() = StatusCode::UNSUPPORTED_MEDIA_TYPE;
-                res
+        };
+
+        res.extensions_mut().insert(rejected);
+        res
+    }
+}
+
+/// Format of the body of a rejection response, see
+/// [`XmlRejection::into_response_with`]
+///
+/// Can be extracted from the request to choose the format from the `Accept`
+/// header, for handlers that return a uniform error envelope:
+///
+/// ```rust,no_run
+/// use axum::response::{IntoResponse, Response};
+/// use axum_xml_up::{RejectionFormat, Xml, XmlRejection};
+///
+/// async fn create_user(
+///     format: RejectionFormat,
+///     payload: Result<Xml<()>, XmlRejection>,
+/// ) -> Response {
+///     match payload {
+///         Ok(Xml(_)) => ().into_response(),
+///         Err(rejection) => rejection.into_response_with(format),
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RejectionFormat {
+    /// Plain text message, the format used by [`IntoResponse`]
+    #[default]
+    Text,
+    /// `<error>` element with `code` and `message` child elements
+    Xml,
+    /// JSON object with `code` and `message` fields
+    ///
+    /// Requires the `json` feature.
+    #[cfg(feature = "json")]
+    Json,
+}
+
+impl RejectionFormat {
+    /// Chooses the format from the first media type in the `Accept` headers
+    /// that is XML or JSON (with the `json` feature), defaulting to
+    /// [`RejectionFormat::Text`]
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        crate::accept::media_ranges(headers)
+            // Media types with a zero quality value are not acceptable
+            .filter(|(_, quality)| *quality > 0.0)
+            .find_map(|(mime, _)| {
+                #[cfg(feature = "json")]
+                {
+                    let suffix = mime.suffix().map(|suffix| suffix.as_str());
+                    if mime.subtype() == mime::JSON || suffix == Some("json") {
+                        return Some(Self::Json);
+                    }
+                }
+                crate::is_xml_type(&mime).then_some(Self::Xml)
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl<S> FromRequestParts<S> for RejectionFormat
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    fn from_request_parts<'parts, 'state, 'future>(
+        parts: &'parts mut Parts,
+        _state: &'state S,
+    ) -> Pin<Box<dyn Future<Output = Result<Self, Self::Rejection>> + Send + 'future>>
+    where
+        'parts: 'future,
+        'state: 'future,
+        Self: 'future,
+    {
+        Box::pin(async move { Ok(Self::from_headers(&parts.headers)) })
+    }
+}
+
+/// Structured body of a rejection response
+#[derive(Serialize)]
+#[serde(rename = "error")]
+struct ErrorBody {
+    /// Name of the rejection variant
+    code: &'static str,
+    /// Message describing the rejection
+    message: String,
+    /// Offset of the first syntax error in the body
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offset: Option<usize>,
+}
+
+impl XmlRejection {
+    /// Creates the response for the rejection with the body written in the
+    /// provided `format`. The status code and extensions are the same as the
+    /// response from [`IntoResponse`], which uses [`RejectionFormat::Text`].
+    ///
+    /// The structured formats contain the `code` of the rejection, which is the
+    /// [`XmlRejection::variant_name`], the `message` and for syntax errors the
+    /// `offset` of the error in the body.
+    ///
+    /// ```
+    /// use axum_xml_up::{RejectionFormat, XmlRejection};
+    ///
+    /// let response = XmlRejection::MissingXMLContentType.into_response_with(RejectionFormat::Xml);
+    /// assert_eq!(response.status(), 415);
+    /// assert_eq!(response.headers()["content-type"], "application/xml");
+    /// ```
+    pub fn into_response_with(self, format: RejectionFormat) -> Response {
+        let (content_type, body) = match format {
+            RejectionFormat::Text => return self.into_response(),
+            RejectionFormat::Xml => {
+                let body = ErrorBody::from(&self);
+                let body = quick_xml::se::to_string(&body)
+                    .expect("Serializing the error body should never fail");
+                ("application/xml", body)
             }
-            XmlRejection::BytesRejection(e) => e.into_response(),
+            #[cfg(feature = "json")]
+            RejectionFormat::Json => {
+                let body = serde_json::to_string(&ErrorBody::from(&self))
+                    .expect("Serializing the error body should never fail");
+                ("application/json", body)
+            }
+        };
+
+        let (mut parts, _) = self.into_response().into_parts();
+        parts
+            .headers
+            .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+        Response::from_parts(parts, Body::new(body))
+    }
+}
+
+impl From<&XmlRejection> for ErrorBody {
+    fn from(rejection: &XmlRejection) -> Self {
+        let offset = match rejection {
+            XmlRejection::InvalidXMLBodyAt { offset, .. } => Some(*offset),
+            _ => None,
+        };
+
+        Self {
+            code: rejection.variant_name(),
+            message: rejection.to_string(),
+            offset,
         }
     }
 }
@@ -0,0 +1,165 @@
+use std::borrow::Cow;
+
+/// Elements that never have content, start tags for these elements are
+/// rewritten as empty elements when repairing
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Repairs common mistakes in documents from buggy producers, see
+/// [`XmlConfig::repair_malformed`](crate::XmlConfig::repair_malformed)
+///
+/// * Ampersands that don't start a character or entity reference are
+///   escaped as `&amp;`
+/// * Start tags of [`VOID_ELEMENTS`] are written as empty elements and their
+///   end tags are removed
+///
+/// Comments, CDATA sections and processing instructions are left unchanged.
+pub(crate) fn repair(bytes: &[u8]) -> Cow<'_, [u8]> {
+    if !needs_repair(bytes) {
+        return Cow::Borrowed(bytes);
+    }
+
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let rest = &bytes[index..];
+
+        // Sections which are copied unchanged up to their terminator
+        let section = [
+            (&b"<!--"[..], &b"-->"[..]),
+            (b"<![CDATA[", b"]]>"),
+            (b"<?", b"?>"),
+        ]
+        .into_iter()
+        .find(|(start, _)| rest.starts_with(start));
+
+        if let Some((start, end)) = section {
+            let len = find(&rest[start.len()..], end)
+                .map_or(rest.len(), |offset| start.len() + offset + end.len());
+            out.extend_from_slice(&rest[..len]);
+            index += len;
+            continue;
+        }
+
+        match rest[0] {
+            b'<' => {
+                let len = tag_len(rest);
+                repair_tag(&rest[..len], &mut out);
+                index += len;
+            }
+            b'&' => {
+                if is_reference(rest) {
+                    out.push(b'&');
+                } else {
+                    out.extend_from_slice(b"&amp;");
+                }
+                index += 1;
+            }
+            byte => {
+                out.push(byte);
+                index += 1;
+            }
+        }
+    }
+
+    Cow::Owned(out)
+}
+
+/// Whether the document contains anything that could need repairing
+fn needs_repair(bytes: &[u8]) -> bool {
+    bytes.contains(&b'&')
+        || bytes.windows(2).any(|pair| {
+            pair[0] == b'<'
+                && VOID_ELEMENTS
+                    .iter()
+                    .any(|name| name.as_bytes()[0] == pair[1].to_ascii_lowercase())
+        })
+}
+
+/// Writes the `tag` to `out`, rewriting the tags of void elements
+fn repair_tag(tag: &[u8], out: &mut Vec<u8>) {
+    let is_end = tag.starts_with(b"</");
+    let name_start = if is_end { 2 } else { 1 };
+    let name = &tag[name_start..];
+    let name_len = name
+        .iter()
+        .position(|byte| byte.is_ascii_whitespace() || matches!(byte, b'/' | b'>'))
+        .unwrap_or(name.len());
+    let name = &name[..name_len];
+
+    let is_void = VOID_ELEMENTS
+        .iter()
+        .any(|void| void.as_bytes().eq_ignore_ascii_case(name));
+
+    if is_void && is_end {
+        return;
+    }
+
+    let start = out.len();
+    for (index, &byte) in tag.iter().enumerate() {
+        if byte == b'&' && !is_reference(&tag[index..]) {
+            out.extend_from_slice(b"&amp;");
+        } else {
+            out.push(byte);
+        }
+    }
+
+    if is_void && out.ends_with(b">") && !out.ends_with(b"/>") && out.len() - start > 1 {
+        out.pop();
+        out.extend_from_slice(b"/>");
+    }
+}
+
+/// Length of the tag at the start of `bytes`, including the closing `>`
+/// unless the document ends first. Quoted attribute values may contain `>`.
+fn tag_len(bytes: &[u8]) -> usize {
+    let mut quote = None;
+    for (index, &byte) in bytes.iter().enumerate().skip(1) {
+        match (quote, byte) {
+            (None, b'"' | b'\'') => quote = Some(byte),
+            (Some(open), byte) if open == byte => quote = None,
+            (None, b'>') => return index + 1,
+            _ => {}
+        }
+    }
+    bytes.len()
+}
+
+/// Whether the `&` at the start of `bytes` starts a character reference
+/// (`&#38;`, `&#x26;`) or an entity reference (`&amp;`)
+fn is_reference(bytes: &[u8]) -> bool {
+    // Only the bytes that can be part of a reference are scanned, so the
+    // repair stays linear in the length of the document
+    let len = bytes[1..]
+        .iter()
+        .take_while(|&&byte| {
+            byte.is_ascii_alphanumeric() || matches!(byte, b'#' | b'_' | b':' | b'.' | b'-')
+        })
+        .count();
+    if bytes.get(len + 1) != Some(&b';') {
+        return false;
+    }
+    let reference = &bytes[1..len + 1];
+
+    match reference {
+        [b'#', b'x', digits @ ..] => !digits.is_empty() && digits.iter().all(u8::is_ascii_hexdigit),
+        [b'#', digits @ ..] => !digits.is_empty() && digits.iter().all(u8::is_ascii_digit),
+        [first, rest @ ..] => {
+            (first.is_ascii_alphabetic() || matches!(first, b'_' | b':'))
+                && rest.iter().all(|byte| {
+                    byte.is_ascii_alphanumeric() || matches!(byte, b'_' | b':' | b'.' | b'-')
+                })
+        }
+        [] => false,
+    }
+}
+
+/// Finds the offset of `needle` within `haystack`
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
@@ -0,0 +1,772 @@
+#[cfg(feature = "compression")]
+use crate::compression::{compress, AcceptEncoding};
+use crate::range::ByteRange;
+use crate::value::{XmlNode, XmlValue};
+use crate::Xml;
+use axum_core::response::{IntoResponse, Response};
+use bytes::{Bytes, BytesMut};
+use http::{header, HeaderValue, StatusCode};
+use quick_xml::se::Serializer;
+use quick_xml::DeError;
+use serde::Serialize;
+use std::fmt::{self, Write};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// Default name of the root element for values without an element name
+const DEFAULT_PRIMITIVE_ROOT: &str = "value";
+
+/// XML declaration written by [`XmlResponse::with_declaration`], the body is
+/// always written as UTF-8
+const DECLARATION: &str = r#"<?xml version="1.0" encoding="UTF-8"?>"#;
+
+/// Content type used by [`XmlResponse::with_charset`]
+const CONTENT_TYPE_UTF_8: &str = "application/xml; charset=utf-8";
+
+/// Policy used by [`XmlResponse::content_security_policy`], browsers load XSLT
+/// stylesheets under `script-src` and only from the origin of the document so
+/// it can't be sandboxed
+const CONTENT_SECURITY_POLICY: &str =
+    "default-src 'none'; script-src 'self'; style-src 'self'; img-src 'self'";
+
+/// XML response with additional serialization options
+///
+/// Created using [`XmlResponse::new`] or by calling one of the option methods
+/// on [`Xml`] directly. Serializes the same way as [`Xml`] when no options are
+/// set.
+///
+/// ```
+/// use axum_xml_up::{Xml, XmlResponse};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     #[serde(rename = "@id")]
+///     id: u32,
+///     name: String,
+/// }
+///
+/// async fn get_user() -> XmlResponse<User> {
+///     let user = User { id: 1, name: "Jacob".to_string() };
+///     Xml(user)
+///         .namespace("u", "urn:example:user")
+///         .field_prefix("name", "u")
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct XmlResponse<T> {
+    /// The value to serialize
+    value: T,
+    /// Namespace prefix declarations to add to the root element
+    namespaces: Vec<(String, String)>,
+    /// Mapping from field element names to the prefix to apply
+    field_prefixes: Vec<(String, String)>,
+    /// Names of the collection fields emitted as an empty element when empty
+    empty_collections: Vec<String>,
+    /// Name of the empty root element emitted for values that serialize to nothing
+    empty_root: Option<String>,
+    /// Name of the root element used instead of the name of the value
+    root_name: Option<String>,
+    /// Name of the root element wrapping values without an element name
+    primitive_root: String,
+    /// Character and width used to indent the body
+    indent: Option<(char, usize)>,
+    /// Whether the body starts with an XML declaration
+    declaration: bool,
+    /// Whether the body ends with a newline
+    trailing_newline: bool,
+    /// Whether the content type includes the `charset=utf-8` parameter
+    charset: bool,
+    /// Whether non-ASCII characters are written as character references
+    ascii_only: bool,
+    /// Range of the body to respond with
+    range: Option<ByteRange>,
+    /// Validator the serialized body is checked with
+    validator: Option<Validator>,
+    /// Whether the restrictive content security policy headers are set
+    content_security_policy: bool,
+    /// Status of the response when the body is sent in full
+    status: StatusCode,
+    /// Time after which the response is considered stale
+    expires: Option<SystemTime>,
+    /// Whether the body is gzip compressed
+    #[cfg(feature = "compression")]
+    compressed: bool,
+    /// Whether compression was negotiated from the request `Accept-Encoding`
+    #[cfg(feature = "compression")]
+    negotiated: bool,
+}
+
+impl<T> XmlResponse<T> {
+    /// Creates a response for `value` without any options applied
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            namespaces: Vec::new(),
+            field_prefixes: Vec::new(),
+            empty_collections: Vec::new(),
+            empty_root: None,
+            root_name: None,
+            primitive_root: DEFAULT_PRIMITIVE_ROOT.to_string(),
+            indent: None,
+            declaration: false,
+            trailing_newline: false,
+            charset: false,
+            ascii_only: false,
+            range: None,
+            validator: None,
+            content_security_policy: false,
+            status: StatusCode::OK,
+            expires: None,
+            #[cfg(feature = "compression")]
+            compressed: false,
+            #[cfg(feature = "compression")]
+            negotiated: false,
+        }
+    }
+
+    /// Declares the namespace `prefix` for `uri` on the root element
+    /// (`xmlns:prefix="uri"`)
+    pub fn namespace(mut self, prefix: impl Into<String>, uri: impl Into<String>) -> Self {
+        self.namespaces.push((prefix.into(), uri.into()));
+        self
+    }
+
+    /// Declares each of the `(prefix, uri)` pairs on the root element, the same
+    /// as calling [`XmlResponse::namespace`] for each pair
+    ///
+    /// ```
+    /// use axum_xml_up::{Xml, XmlResponse};
+    ///
+    /// async fn get_count() -> XmlResponse<Vec<u32>> {
+    ///     Xml(vec![1]).namespaces([("a", "urn:example:a"), ("b", "urn:example:b")])
+    /// }
+    /// ```
+    pub fn namespaces<I, P, U>(mut self, namespaces: I) -> Self
+    where
+        I: IntoIterator<Item = (P, U)>,
+        P: Into<String>,
+        U: Into<String>,
+    {
+        self.namespaces.extend(
+            namespaces
+                .into_iter()
+                .map(|(prefix, uri)| (prefix.into(), uri.into())),
+        );
+        self
+    }
+
+    /// Emits the elements of the field named `field` with the namespace `prefix`
+    /// (`<prefix:field>`). Only applies to the fields of the root value, the
+    /// prefix should be declared using [`XmlResponse::namespace`].
+    pub fn field_prefix(mut self, field: impl Into<String>, prefix: impl Into<String>) -> Self {
+        self.field_prefixes.push((field.into(), prefix.into()));
+        self
+    }
+
+    /// Emits an empty `<field/>` element when the collection field named `field`
+    /// has no items. Without this option an empty collection serializes to
+    /// nothing, so consumers can't tell an empty collection from an absent one.
+    ///
+    /// Only applies to the fields of the root value. The empty element is added
+    /// after the other children of the root element.
+    ///
+    /// ```
+    /// use axum_xml_up::{Xml, XmlResponse};
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Order {
+    ///     items: Vec<String>,
+    /// }
+    ///
+    /// async fn get_order() -> XmlResponse<Order> {
+    ///     // <Order><items/></Order>
+    ///     Xml(Order { items: Vec::new() }).empty_collection("items")
+    /// }
+    /// ```
+    pub fn empty_collection(mut self, field: impl Into<String>) -> Self {
+        self.empty_collections.push(field.into());
+        self
+    }
+
+    /// Emits an empty `<name/>` root element when the value serializes to an
+    /// empty document, such as `None`. Without this option `Some(value)`
+    /// serializes as `value` and `None` produces an empty body.
+    pub fn empty_as(mut self, name: impl Into<String>) -> Self {
+        self.empty_root = Some(name.into());
+        self
+    }
+
+    /// Uses `name` for the root element instead of the name of the type, so
+    /// `Xml(User { .. }).root_name("user")` serializes as `<user>..</user>`.
+    /// Values without an element name of their own are also wrapped in an
+    /// element with this name.
+    pub fn root_name(mut self, name: impl Into<String>) -> Self {
+        self.root_name = Some(name.into());
+        self
+    }
+
+    /// Sets the name of the root element wrapping values that don't have an
+    /// element name of their own, such as numbers and strings. Defaults to
+    /// `value`, so `Xml(42)` serializes as `<value>42</value>`.
+    ///
+    /// Items of a sequence of such values are each wrapped in an element with
+    /// this name.
+    pub fn primitive_root(mut self, name: impl Into<String>) -> Self {
+        self.primitive_root = name.into();
+        self
+    }
+
+    /// Indents the elements of the body with two spaces for each level of
+    /// nesting, making the output easier for humans to read
+    pub fn pretty(self) -> Self {
+        self.indent(' ', 2)
+    }
+
+    /// Indents the elements of the body with `width` repetitions of `ch` for
+    /// each level of nesting, such as `indent('\t', 1)` to indent with tabs
+    pub fn indent(mut self, ch: char, width: usize) -> Self {
+        self.indent = Some((ch, width));
+        self
+    }
+
+    /// Sets whether the body starts with the XML declaration
+    /// `<?xml version="1.0" encoding="UTF-8"?>`, which is required by some
+    /// consumers such as SOAP clients. Disabled by default.
+    pub fn with_declaration(mut self, declaration: bool) -> Self {
+        self.declaration = declaration;
+        self
+    }
+
+    /// Sets whether a newline is written after the root element, for tooling
+    /// and diff based tests expecting files to end with a newline. Disabled by
+    /// default.
+    pub fn trailing_newline(mut self, newline: bool) -> Self {
+        self.trailing_newline = newline;
+        self
+    }
+
+    /// Sets whether the content type of the response includes the charset, as
+    /// `application/xml; charset=utf-8`, for clients that expect it. The body
+    /// is always written as UTF-8. Disabled by default, using
+    /// `application/xml`.
+    pub fn with_charset(mut self, charset: bool) -> Self {
+        self.charset = charset;
+        self
+    }
+
+    /// Writes all non-ASCII characters as numeric character references
+    /// (`é` as `&#xE9;`) so the body survives channels that mangle non-ASCII
+    /// bytes. Element and attribute names are expected to be ASCII.
+    pub fn ascii_only(mut self) -> Self {
+        self.ascii_only = true;
+        self
+    }
+
+    /// Checks the serialized body using `validator` before it is sent, bodies
+    /// that fail validation are replaced with a `500 Internal Server Error`
+    /// response describing the failure.
+    ///
+    /// Acts as a safety net for serialization bugs in critical APIs, such as
+    /// validating the body against an XSD schema using a schema validation
+    /// library.
+    ///
+    /// ```
+    /// use axum_xml_up::{Xml, XmlResponse, XmlValue};
+    ///
+    /// async fn get_count() -> XmlResponse<u32> {
+    ///     Xml(42).validate_with(|body| {
+    ///         let root = XmlValue::from_bytes(body).map_err(|err| err.to_string())?;
+    ///         match root.name.as_str() {
+    ///             "value" => Ok(()),
+    ///             name => Err(format!("unexpected root element `{name}`")),
+    ///         }
+    ///     })
+    /// }
+    /// ```
+    pub fn validate_with<F, E>(mut self, validator: F) -> Self
+    where
+        F: Fn(&[u8]) -> Result<(), E> + Send + Sync + 'static,
+        E: fmt::Display,
+    {
+        self.validator = Some(Validator(Arc::new(move |body| {
+            validator(body).map_err(|err| err.to_string())
+        })));
+        self
+    }
+
+    /// Sets a restrictive `Content-Security-Policy` header along with
+    /// `X-Content-Type-Options: nosniff` for XML served to browsers, such as
+    /// documents styled using XSLT. Scripts (which includes XSLT stylesheets),
+    /// stylesheets and images are only loaded from the same origin while
+    /// plugins, frames and any other resources are blocked:
+    ///
+    /// ```text
+    /// default-src 'none'; script-src 'self'; style-src 'self'; img-src 'self'
+    /// ```
+    pub fn content_security_policy(mut self) -> Self {
+        self.content_security_policy = true;
+        self
+    }
+
+    /// Responds with the provided `status` instead of `200 OK`, such as
+    /// `201 Created` along with the created resource. The status isn't used
+    /// for partial responses to a [`XmlResponse::range`] or when serializing
+    /// the value fails.
+    ///
+    /// ```
+    /// use axum::http::StatusCode;
+    /// use axum_xml_up::{Xml, XmlResponse};
+    ///
+    /// async fn create_user() -> XmlResponse<Vec<u32>> {
+    ///     Xml(vec![1]).with_status(StatusCode::CREATED)
+    /// }
+    /// ```
+    pub fn with_status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Sets the `Expires` header to `time` formatted as an HTTP date, for
+    /// caches that don't understand `Cache-Control`.
+    ///
+    /// ```
+    /// use axum_xml_up::{Xml, XmlResponse};
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// async fn get_rates() -> XmlResponse<Vec<u32>> {
+    ///     Xml(vec![1]).expires_at(SystemTime::now() + Duration::from_secs(3600))
+    /// }
+    /// ```
+    pub fn expires_at(mut self, time: SystemTime) -> Self {
+        self.expires = Some(time);
+        self
+    }
+
+    /// Responds with only the requested `range` of the serialized body using a
+    /// `206 Partial Content` response, or `416 Range Not Satisfiable` when the
+    /// range is outside of the body. The full body is sent when no range was
+    /// requested. The response also gets an `Accept-Ranges: bytes` header.
+    ///
+    /// The range applies to the body after compression, so the same
+    /// compression options should be used when serving each range.
+    pub fn range(mut self, range: ByteRange) -> Self {
+        self.range = Some(range);
+        self
+    }
+
+    /// Compresses the serialized body using gzip and sets the
+    /// `Content-Encoding: gzip` header.
+    ///
+    /// Compression layers such as `tower_http::compression` skip responses that
+    /// already have a `Content-Encoding` so the body is not compressed twice.
+    ///
+    /// Requires the `compression` feature.
+    #[cfg(feature = "compression")]
+    pub fn compressed(mut self) -> Self {
+        self.compressed = true;
+        self
+    }
+
+    /// Compresses the body like [`XmlResponse::compressed`] only when the client
+    /// accepts gzip, the response also gets a `Vary: Accept-Encoding` header so
+    /// caches keep the compressed and plain responses apart.
+    ///
+    /// Requires the `compression` feature.
+    #[cfg(feature = "compression")]
+    pub fn compressed_if_accepted(mut self, accept: AcceptEncoding) -> Self {
+        self.compressed = accept.gzip();
+        self.negotiated = true;
+        self
+    }
+}
+
+impl<T> XmlResponse<T>
+where
+    T: Serialize,
+{
+    /// Serializes the value applying the response options
+    pub(crate) fn serialize(&self) -> Result<Bytes, DeError> {
+        // Serialize directly into the bytes buffer used for the body
+        let mut buf = BytesMut::with_capacity(128);
+        match self.write(&mut buf, self.root_name.as_deref()) {
+            // Values without an element name are wrapped in the primitive root
+            Err(DeError::Unsupported(_)) => {
+                buf.clear();
+                self.write(&mut buf, Some(&self.primitive_root))?;
+            }
+            result => result?,
+        }
+
+        if buf.is_empty() {
+            if let Some(name) = &self.empty_root {
+                buf.extend_from_slice(&XmlValue::new(name.as_str()).to_bytes());
+            }
+        }
+
+        let body = if self.namespaces.is_empty()
+            && self.field_prefixes.is_empty()
+            && self.empty_collections.is_empty()
+        {
+            buf.freeze()
+        } else {
+            // Options that modify the document structure are applied to the tree
+            let mut root = XmlValue::from_bytes(&buf)?;
+            self.apply_empty_collections(&mut root);
+            self.apply_namespaces(&mut root);
+            Bytes::from(root.to_bytes())
+        };
+
+        let body = if self.declaration || self.trailing_newline {
+            let mut buf = BytesMut::with_capacity(DECLARATION.len() + body.len() + 1);
+            if self.declaration {
+                buf.extend_from_slice(DECLARATION.as_bytes());
+            }
+            buf.extend_from_slice(&body);
+            if self.trailing_newline {
+                buf.extend_from_slice(b"\n");
+            }
+            buf.freeze()
+        } else {
+            body
+        };
+
+        if let Some(validator) = &self.validator {
+            (validator.0)(&body)
+                .map_err(|err| DeError::Custom(format!("Response failed validation: {err}")))?;
+        }
+
+        let body = if self.ascii_only {
+            escape_non_ascii(&body)
+        } else {
+            body
+        };
+
+        #[cfg(feature = "compression")]
+        if self.compressed {
+            return Ok(Bytes::from(compress(&body)));
+        }
+
+        Ok(body)
+    }
+
+    /// Writes the value to `buf` using the provided `root` element name
+    fn write(&self, buf: &mut BytesMut, root: Option<&str>) -> Result<(), DeError> {
+        let mut serializer = match root {
+            Some(root) => Serializer::with_root(buf, Some(root))?,
+            None => Serializer::new(buf),
+        };
+
+        if let Some((ch, width)) = self.indent {
+            serializer.indent(ch, width);
+        }
+
+        self.value.serialize(serializer)
+    }
+
+    /// Adds an empty element to the `root` for each of the empty collection
+    /// fields that have no elements
+    fn apply_empty_collections(&self, root: &mut XmlValue) {
+        for field in &self.empty_collections {
+            if !root.elements().any(|element| element.name == *field) {
+                root.children
+                    .push(XmlNode::Element(XmlValue::new(field.as_str())));
+            }
+        }
+    }
+
+    /// Applies the namespace declarations and field prefixes to the `root`
+    fn apply_namespaces(&self, root: &mut XmlValue) {
+        for (prefix, uri) in &self.namespaces {
+            root.attributes
+                .push((format!("xmlns:{prefix}"), uri.to_string()));
+        }
+
+        for child in &mut root.children {
+            let XmlNode::Element(element) = child else {
+                continue;
+            };
+
+            if let Some((_, prefix)) = self
+                .field_prefixes
+                .iter()
+                .find(|(field, _)| *field == element.name)
+            {
+                element.name = format!("{prefix}:{}", element.name);
+            }
+        }
+    }
+}
+
+/// Replaces the non-ASCII characters of `body` with numeric character references
+fn escape_non_ascii(body: &[u8]) -> Bytes {
+    let body = String::from_utf8_lossy(body);
+    let mut escaped = String::with_capacity(body.len());
+    for value in body.chars() {
+        if value.is_ascii() {
+            escaped.push(value);
+        } else {
+            let _ = write!(escaped, "&#x{:X};", value as u32);
+        }
+    }
+    Bytes::from(escaped)
+}
+
+/// Validator for serialized bodies, see [`XmlResponse::validate_with`]
+#[derive(Clone)]
+struct Validator(Arc<ValidatorFn>);
+
+/// Function validating a serialized body
+type ValidatorFn = dyn Fn(&[u8]) -> Result<(), String> + Send + Sync;
+
+impl fmt::Debug for Validator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Validator")
+    }
+}
+
+impl<T> From<Xml<T>> for XmlResponse<T> {
+    fn from(Xml(value): Xml<T>) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T> Xml<T> {
+    /// Shorthand for [`XmlResponse::namespace`]
+    pub fn namespace(self, prefix: impl Into<String>, uri: impl Into<String>) -> XmlResponse<T> {
+        XmlResponse::from(self).namespace(prefix, uri)
+    }
+
+    /// Shorthand for [`XmlResponse::namespaces`]
+    pub fn namespaces<I, P, U>(self, namespaces: I) -> XmlResponse<T>
+    where
+        I: IntoIterator<Item = (P, U)>,
+        P: Into<String>,
+        U: Into<String>,
+    {
+        XmlResponse::from(self).namespaces(namespaces)
+    }
+
+    /// Shorthand for [`XmlResponse::field_prefix`]
+    pub fn field_prefix(
+        self,
+        field: impl Into<String>,
+        prefix: impl Into<String>,
+    ) -> XmlResponse<T> {
+        XmlResponse::from(self).field_prefix(field, prefix)
+    }
+
+    /// Shorthand for [`XmlResponse::empty_collection`]
+    pub fn empty_collection(self, field: impl Into<String>) -> XmlResponse<T> {
+        XmlResponse::from(self).empty_collection(field)
+    }
+
+    /// Shorthand for [`XmlResponse::empty_as`]
+    pub fn empty_as(self, name: impl Into<String>) -> XmlResponse<T> {
+        XmlResponse::from(self).empty_as(name)
+    }
+
+    /// Shorthand for [`XmlResponse::root_name`]
+    pub fn root_name(self, name: impl Into<String>) -> XmlResponse<T> {
+        XmlResponse::from(self).root_name(name)
+    }
+
+    /// Shorthand for [`XmlResponse::primitive_root`]
+    pub fn primitive_root(self, name: impl Into<String>) -> XmlResponse<T> {
+        XmlResponse::from(self).primitive_root(name)
+    }
+
+    /// Shorthand for [`XmlResponse::pretty`]
+    pub fn pretty(self) -> XmlResponse<T> {
+        XmlResponse::from(self).pretty()
+    }
+
+    /// Shorthand for [`XmlResponse::indent`]
+    pub fn indent(self, ch: char, width: usize) -> XmlResponse<T> {
+        XmlResponse::from(self).indent(ch, width)
+    }
+
+    /// Shorthand for [`XmlResponse::with_declaration`]
+    pub fn with_declaration(self, declaration: bool) -> XmlResponse<T> {
+        XmlResponse::from(self).with_declaration(declaration)
+    }
+
+    /// Shorthand for [`XmlResponse::trailing_newline`]
+    pub fn trailing_newline(self, newline: bool) -> XmlResponse<T> {
+        XmlResponse::from(self).trailing_newline(newline)
+    }
+
+    /// Shorthand for [`XmlResponse::with_charset`]
+    pub fn with_charset(self, charset: bool) -> XmlResponse<T> {
+        XmlResponse::from(self).with_charset(charset)
+    }
+
+    /// Shorthand for [`XmlResponse::ascii_only`]
+    pub fn ascii_only(self) -> XmlResponse<T> {
+        XmlResponse::from(self).ascii_only()
+    }
+
+    /// Shorthand for [`XmlResponse::validate_with`]
+    pub fn validate_with<F, E>(self, validator: F) -> XmlResponse<T>
+    where
+        F: Fn(&[u8]) -> Result<(), E> + Send + Sync + 'static,
+        E: fmt::Display,
+    {
+        XmlResponse::from(self).validate_with(validator)
+    }
+
+    /// Shorthand for [`XmlResponse::content_security_policy`]
+    pub fn content_security_policy(self) -> XmlResponse<T> {
+        XmlResponse::from(self).content_security_policy()
+    }
+
+    /// Shorthand for [`XmlResponse::with_status`]
+    pub fn with_status(self, status: StatusCode) -> XmlResponse<T> {
+        XmlResponse::from(self).with_status(status)
+    }
+
+    /// Shorthand for [`XmlResponse::expires_at`]
+    pub fn expires_at(self, time: SystemTime) -> XmlResponse<T> {
+        XmlResponse::from(self).expires_at(time)
+    }
+
+    /// Shorthand for [`XmlResponse::range`]
+    pub fn range(self, range: ByteRange) -> XmlResponse<T> {
+        XmlResponse::from(self).range(range)
+    }
+
+    /// Shorthand for [`XmlResponse::compressed`]
+    #[cfg(feature = "compression")]
+    pub fn compressed(self) -> XmlResponse<T> {
+        XmlResponse::from(self).compressed()
+    }
+
+    /// Shorthand for [`XmlResponse::compressed_if_accepted`]
+    #[cfg(feature = "compression")]
+    pub fn compressed_if_accepted(self, accept: AcceptEncoding) -> XmlResponse<T> {
+        XmlResponse::from(self).compressed_if_accepted(accept)
+    }
+}
+
+impl<T> IntoResponse for XmlResponse<T>
+where
+    T: Serialize,
+{
+    fn into_response(self) -> Response {
+        let body = match self.serialize() {
+            Ok(value) => value,
+            Err(err) => {
+                #[cfg(feature = "tracing")]
+                tracing::error!(
+                    type_name = std::any::type_name::<T>(),
+                    error = %err,
+                    "failed to serialize XML response"
+                );
+
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    [(
+                        header::CONTENT_TYPE,
+                        HeaderValue::from_static(mime::TEXT_PLAIN_UTF_8.as_ref()),
+                    )],
+                    err.to_string(),
+                )
+                    .into_response();
+            }
+        };
+
+        let mut response = match &self.range {
+            Some(range) => {
+                let mut response = range.respond(body);
+                response
+                    .headers_mut()
+                    .insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+                response
+            }
+            None => body.into_response(),
+        };
+
+        if response.status() == StatusCode::OK {
+            *response.status_mut() = self.status;
+        }
+
+        // Unsatisfiable range responses contain an error message instead of the body
+        if response.status() != StatusCode::RANGE_NOT_SATISFIABLE {
+            let content_type = if self.charset {
+                CONTENT_TYPE_UTF_8
+            } else {
+                "application/xml"
+            };
+            response
+                .headers_mut()
+                .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+        }
+
+        if self.content_security_policy {
+            let headers = response.headers_mut();
+            headers.insert(
+                header::CONTENT_SECURITY_POLICY,
+                HeaderValue::from_static(CONTENT_SECURITY_POLICY),
+            );
+            headers.insert(
+                header::X_CONTENT_TYPE_OPTIONS,
+                HeaderValue::from_static("nosniff"),
+            );
+        }
+
+        if let Some(expires) = self.expires {
+            if let Ok(value) = HeaderValue::from_str(&crate::date::http_date(expires)) {
+                response.headers_mut().insert(header::EXPIRES, value);
+            }
+        }
+
+        #[cfg(feature = "compression")]
+        if self.compressed {
+            response
+                .headers_mut()
+                .insert(header::CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+        }
+
+        #[cfg(feature = "compression")]
+        if self.negotiated {
+            response
+                .headers_mut()
+                .insert(header::VARY, HeaderValue::from_static("accept-encoding"));
+        }
+
+        response
+    }
+}
+
+/// Extension trait for creating XML responses from serializable values
+///
+/// Implemented for all types that implement [`Serialize`], avoiding wrapping
+/// values in [`Xml`] by hand:
+///
+/// ```
+/// use axum_xml_up::{AsXmlResponse, Xml};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// async fn get_user() -> Xml<User> {
+///     User { name: "Jacob".to_string() }.into_xml()
+/// }
+/// ```
+pub trait AsXmlResponse: Serialize + Sized {
+    /// Wraps the value in [`Xml`]
+    fn into_xml(self) -> Xml<Self> {
+        Xml(self)
+    }
+
+    /// Creates an indented response for the value, see [`XmlResponse::pretty`]
+    fn into_xml_pretty(self) -> XmlResponse<Self> {
+        XmlResponse::new(self).pretty()
+    }
+}
+
+impl<T> AsXmlResponse for T where T: Serialize {}
@@ -0,0 +1,62 @@
+//! Serde helpers for `Result` fields written as success or error elements
+//!
+//! A `Result<T, E>` field is written as an element containing either an
+//! `<ok>` element with the success value or an `<err>` element with the error,
+//! such as `<outcome><ok>42</ok></outcome>`. Use with `#[serde(with = "..")]`:
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Deserialize, Serialize)]
+//! struct Job {
+//!     #[serde(with = "axum_xml_up::result")]
+//!     outcome: Result<u32, String>,
+//! }
+//! ```
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Element containing either of the result arms
+#[derive(Deserialize, Serialize)]
+struct Outcome<T, E> {
+    #[serde(rename = "$value")]
+    value: Arm<T, E>,
+}
+
+/// Arms of the result written as `<ok>` and `<err>` elements
+#[derive(Deserialize, Serialize)]
+enum Arm<T, E> {
+    #[serde(rename = "ok")]
+    Ok(T),
+    #[serde(rename = "err")]
+    Err(E),
+}
+
+/// Deserializes a result from an element containing an `<ok>` or `<err>`
+/// element
+pub fn deserialize<'de, D, T, E>(deserializer: D) -> Result<Result<T, E>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+    E: Deserialize<'de>,
+{
+    let outcome = Outcome::<T, E>::deserialize(deserializer)?;
+    Ok(match outcome.value {
+        Arm::Ok(value) => Ok(value),
+        Arm::Err(err) => Err(err),
+    })
+}
+
+/// Serializes a result as an element containing an `<ok>` or `<err>` element
+pub fn serialize<S, T, E>(result: &Result<T, E>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Serialize,
+    E: Serialize,
+{
+    let value = match result {
+        Ok(value) => Arm::Ok(value),
+        Err(err) => Arm::Err(err),
+    };
+    Outcome { value }.serialize(serializer)
+}
@@ -0,0 +1,293 @@
+use crate::config::XmlConfig;
+use crate::rejection::{XmlLimit, XmlRejection};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{DeError, Reader};
+use std::collections::HashSet;
+
+/// Scans over the document events checking the document against the
+/// restrictions in the `config` before it is deserialized
+pub(crate) fn scan(bytes: &[u8], config: &XmlConfig) -> Result<(), XmlRejection> {
+    #[cfg(feature = "encoding")]
+    let mut reader = crate::encoding::reader(bytes);
+    #[cfg(not(feature = "encoding"))]
+    let mut reader = Reader::from_reader(bytes);
+
+    // Checks that need the whole document require scanning past the root element
+    let full_scan = config.max_markup_ratio.is_some()
+        || config.max_depth.is_some()
+        || config.max_comment_len.is_some()
+        || config.max_comments.is_some()
+        || config.max_unique_element_names.is_some()
+        || config.max_entity_expansions.is_some()
+        || config.required_paths.is_some()
+        || config.reject_processing_instructions
+        || config.forbid_doctype;
+
+    let mut found_root = false;
+    // Number of elements in the document
+    let mut elements: usize = 0;
+    // Length of the text and attribute value content in the document
+    let mut content_len: usize = 0;
+    // Nesting depth of the current element
+    let mut depth: usize = 0;
+    // Number of comments in the document
+    let mut comments: usize = 0;
+    // Distinct names of the elements in the document
+    let mut names: HashSet<Vec<u8>> = HashSet::new();
+    // Number of custom entity references in the document
+    let mut entities: usize = 0;
+    // Required paths that have been found in the document
+    let mut paths = config.required_paths.as_deref().map(PathTracker::new);
+
+    loop {
+        let event = reader.read_event().map_err(DeError::from)?;
+        match &event {
+            Event::Start(start) | Event::Empty(start) => {
+                if !found_root {
+                    check_root(&reader, start, config)?;
+                }
+
+                found_root = true;
+                if !full_scan {
+                    return Ok(());
+                }
+
+                if config.max_depth.is_some_and(|max_depth| depth >= max_depth) {
+                    return Err(XmlRejection::LimitExceeded(XmlLimit::Depth));
+                }
+
+                // Empty elements don't contain any children
+                if matches!(event, Event::Start(_)) {
+                    depth += 1;
+                }
+
+                if let Some(max_names) = config.max_unique_element_names {
+                    let name = start.name();
+                    if !names.contains(name.as_ref()) {
+                        if names.len() >= max_names {
+                            return Err(XmlRejection::LimitExceeded(XmlLimit::UniqueNames));
+                        }
+                        names.insert(name.as_ref().to_vec());
+                    }
+                }
+
+                if let Some(paths) = &mut paths {
+                    paths.start(start, matches!(event, Event::Empty(_)));
+                }
+
+                elements += 1;
+                if config.max_markup_ratio.is_some() || config.max_entity_expansions.is_some() {
+                    // Duplicate attributes are rejected when deserializing, checking
+                    // for them here would be quadratic in the number of attributes
+                    for attribute in start.attributes().with_checks(false).flatten() {
+                        content_len += attribute.value.len();
+                        entities += entity_references(&attribute.value);
+                    }
+                }
+            }
+            Event::Text(text) => {
+                content_len += text
+                    .iter()
+                    .filter(|byte| !byte.is_ascii_whitespace())
+                    .count();
+                entities += entity_references(text);
+            }
+            Event::End(_) => {
+                depth = depth.saturating_sub(1);
+                if let Some(paths) = &mut paths {
+                    paths.end();
+                }
+            }
+            Event::Comment(comment) => {
+                if config
+                    .max_comment_len
+                    .is_some_and(|len| comment.len() > len)
+                {
+                    return Err(XmlRejection::LimitExceeded(XmlLimit::CommentLength));
+                }
+
+                comments += 1;
+                if config.max_comments.is_some_and(|count| comments > count) {
+                    return Err(XmlRejection::LimitExceeded(XmlLimit::Comments));
+                }
+            }
+            Event::CData(data) => content_len += data.len(),
+            Event::Decl(decl) if config.require_version_1_0 => {
+                let version = decl.version().map_err(DeError::from)?;
+                if version.as_ref() != b"1.0" {
+                    let version = String::from_utf8_lossy(&version).into_owned();
+                    return Err(XmlRejection::UnsupportedVersion(version));
+                }
+            }
+            Event::PI(_) if config.reject_processing_instructions => {
+                return Err(XmlRejection::ProcessingInstruction);
+            }
+            Event::DocType(_) if config.forbid_doctype => {
+                return Err(XmlRejection::ForbiddenDoctype);
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+
+        if config
+            .max_entity_expansions
+            .is_some_and(|count| entities > count)
+        {
+            return Err(XmlRejection::LimitExceeded(XmlLimit::EntityExpansions));
+        }
+    }
+
+    if !found_root && (config.require_root_element || config.missing_root_as_default) {
+        // Without a root element the content can only be text outside of any element
+        if content_len > 0 {
+            return Err(XmlRejection::NotADocument);
+        }
+        return Err(XmlRejection::NoRootElement);
+    }
+
+    if let Some(path) = paths.as_ref().and_then(PathTracker::missing) {
+        return Err(XmlRejection::MissingRequiredPath(path.to_string()));
+    }
+
+    if let Some(limit) = &config.max_markup_ratio {
+        if elements > limit.min_elements && elements as f64 > limit.ratio * content_len as f64 {
+            return Err(XmlRejection::LimitExceeded(XmlLimit::MarkupRatio));
+        }
+    }
+
+    Ok(())
+}
+
+/// Tracks which of the paths required by [`XmlConfig::required_paths`] are
+/// present in the document
+struct PathTracker<'a> {
+    /// The required paths
+    required: &'a [String],
+    /// Whether each of the required paths was found
+    found: Vec<bool>,
+    /// Path of the current element
+    path: String,
+    /// Length of the path before each of the open elements
+    lengths: Vec<usize>,
+}
+
+impl<'a> PathTracker<'a> {
+    /// Creates a tracker for the `required` paths
+    fn new(required: &'a [String]) -> Self {
+        Self {
+            required,
+            found: vec![false; required.len()],
+            path: String::new(),
+            lengths: Vec::new(),
+        }
+    }
+
+    /// Enters the element `start` marking the paths of it and its attributes
+    /// as found, `empty` elements are left straight away
+    fn start(&mut self, start: &BytesStart, empty: bool) {
+        self.lengths.push(self.path.len());
+        if !self.path.is_empty() {
+            self.path.push('/');
+        }
+        self.path
+            .push_str(&String::from_utf8_lossy(start.name().as_ref()));
+        self.mark();
+
+        for attribute in start.attributes().with_checks(false).flatten() {
+            let len = self.path.len();
+            self.path.push_str("/@");
+            self.path
+                .push_str(&String::from_utf8_lossy(attribute.key.as_ref()));
+            self.mark();
+            self.path.truncate(len);
+        }
+
+        if empty {
+            self.end();
+        }
+    }
+
+    /// Leaves the current element
+    fn end(&mut self) {
+        if let Some(len) = self.lengths.pop() {
+            self.path.truncate(len);
+        }
+    }
+
+    /// Marks the current path as found
+    fn mark(&mut self) {
+        for (required, found) in self.required.iter().zip(&mut self.found) {
+            if *required == self.path {
+                *found = true;
+            }
+        }
+    }
+
+    /// The first required path that was not found
+    fn missing(&self) -> Option<&str> {
+        self.required
+            .iter()
+            .zip(&self.found)
+            .find(|(_, found)| !**found)
+            .map(|(path, _)| path.as_str())
+    }
+}
+
+/// Counts the references to entities other than the predefined entities and
+/// character references in the escaped `bytes`
+fn entity_references(bytes: &[u8]) -> usize {
+    bytes
+        .split(|&byte| byte == b'&')
+        .skip(1)
+        .filter_map(|reference| {
+            let end = reference.iter().position(|&byte| byte == b';')?;
+            Some(&reference[..end])
+        })
+        .filter(|name| {
+            !name.starts_with(b"#") && !matches!(*name, b"lt" | b"gt" | b"amp" | b"apos" | b"quot")
+        })
+        .count()
+}
+
+/// Reads through all of the document events checking that the document is
+/// well-formed, including that every element is closed
+pub(crate) fn well_formed(bytes: &[u8]) -> Result<(), DeError> {
+    let mut reader = Reader::from_reader(bytes);
+    reader.check_end_names(true);
+
+    let mut depth: usize = 0;
+    loop {
+        match reader.read_event()? {
+            Event::Start(_) => depth += 1,
+            Event::End(_) => depth = depth.saturating_sub(1),
+            Event::Eof if depth > 0 => return Err(DeError::UnexpectedEof),
+            Event::Eof => return Ok(()),
+            _ => {}
+        }
+    }
+}
+
+/// Checks the name of the `root` element against the allowed root elements
+fn check_root(
+    reader: &Reader<&[u8]>,
+    root: &BytesStart,
+    config: &XmlConfig,
+) -> Result<(), XmlRejection> {
+    let Some(allowed) = &config.allowed_roots else {
+        return Ok(());
+    };
+
+    let name = root.name();
+    let name = reader
+        .decoder()
+        .decode(name.as_ref())
+        .map_err(DeError::from)?;
+    if allowed.iter().any(|allowed| *allowed == name) {
+        return Ok(());
+    }
+
+    Err(XmlRejection::RootNotAllowed {
+        found: name.into_owned(),
+        allowed: allowed.clone(),
+    })
+}
@@ -0,0 +1,115 @@
+//! Serde helpers for attribute values containing a list of separated values
+//!
+//! Attributes such as `coords="1 2 3"` or `tags="a,b,c"` can be deserialized
+//! into a `Vec<T>` by splitting the value on a separator, each item is parsed
+//! using its [`FromStr`] implementation. The [`space`] (splitting on any
+//! whitespace) and [`comma`] modules can be used with `#[serde(with = "..")]`,
+//! other separators can be provided to the generic [`deserialize`] and
+//! [`serialize`] functions:
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Deserialize, Serialize)]
+//! struct Polygon {
+//!     #[serde(rename = "@coords", with = "axum_xml_up::separated::space")]
+//!     coords: Vec<f64>,
+//!     #[serde(
+//!         rename = "@tags",
+//!         deserialize_with = "axum_xml_up::separated::deserialize::<_, _, ';'>",
+//!         serialize_with = "axum_xml_up::separated::serialize::<_, _, ';'>"
+//!     )]
+//!     tags: Vec<String>,
+//! }
+//! ```
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serializer};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Deserializes a value separated by `SEP` into a list of items, empty items
+/// (such as from repeated separators) are skipped
+pub fn deserialize<'de, D, T, const SEP: char>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    let value = String::deserialize(deserializer)?;
+    value
+        .split(SEP)
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| item.parse().map_err(D::Error::custom))
+        .collect()
+}
+
+/// Serializes a list of items as a single value separated by `SEP`
+pub fn serialize<S, T, const SEP: char>(values: &[T], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Display,
+{
+    let mut value = String::new();
+    for (index, item) in values.iter().enumerate() {
+        if index > 0 {
+            value.push(SEP);
+        }
+        value.push_str(&item.to_string());
+    }
+    serializer.serialize_str(&value)
+}
+
+/// Values separated by whitespace (`"1 2 3"`), as in XML list types
+pub mod space {
+    use super::*;
+
+    /// Deserializes a value separated by any ASCII whitespace, including tabs
+    /// and line breaks, see [`super::deserialize`]
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: FromStr,
+        T::Err: Display,
+    {
+        let value = String::deserialize(deserializer)?;
+        value
+            .split_ascii_whitespace()
+            .map(|item| item.parse().map_err(D::Error::custom))
+            .collect()
+    }
+
+    /// Serializes a space separated value, see [`super::serialize`]
+    pub fn serialize<S, T>(values: &[T], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Display,
+    {
+        super::serialize::<S, T, ' '>(values, serializer)
+    }
+}
+
+/// Values separated by commas (`"1,2,3"`)
+pub mod comma {
+    use super::*;
+
+    /// Deserializes a comma separated value, see [`super::deserialize`]
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: FromStr,
+        T::Err: Display,
+    {
+        super::deserialize::<D, T, ','>(deserializer)
+    }
+
+    /// Serializes a comma separated value, see [`super::serialize`]
+    pub fn serialize<S, T>(values: &[T], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Display,
+    {
+        super::serialize::<S, T, ','>(values, serializer)
+    }
+}
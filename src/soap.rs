@@ -0,0 +1,82 @@
+use crate::response::XmlResponse;
+use axum_core::response::{IntoResponse, Response};
+use bytes::BytesMut;
+use http::{header, HeaderValue, StatusCode};
+use serde::Serialize;
+
+/// Start of the envelope up to the contents of the body element, declaring
+/// the SOAP 1.2 envelope namespace
+const ENVELOPE_START: &str =
+    r#"<env:Envelope xmlns:env="http://www.w3.org/2003/05/soap-envelope"><env:Body>"#;
+
+/// End of the envelope after the contents of the body element
+const ENVELOPE_END: &str = "</env:Body></env:Envelope>";
+
+/// SOAP 1.2 response
+///
+/// Serializes the value the same way as [`Xml`](crate::Xml) and wraps it in
+/// the `Body` of a SOAP 1.2 envelope, setting the
+/// `Content-Type: application/soap+xml` header.
+///
+/// ```
+/// use axum_xml_up::Soap;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct GetUserResponse {
+///     name: String,
+/// }
+///
+/// async fn get_user() -> Soap<GetUserResponse> {
+///     // <env:Envelope xmlns:env="http://www.w3.org/2003/05/soap-envelope">
+///     //   <env:Body><GetUserResponse><name>Jacob</name></GetUserResponse></env:Body>
+///     // </env:Envelope>
+///     Soap::response(GetUserResponse { name: "Jacob".to_string() })
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Soap<T>(pub T);
+
+impl<T> Soap<T> {
+    /// Creates a response wrapping `value` in a SOAP envelope
+    pub fn response(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> IntoResponse for Soap<T>
+where
+    T: Serialize,
+{
+    fn into_response(self) -> Response {
+        let content = match XmlResponse::new(self.0).serialize() {
+            Ok(value) => value,
+            Err(err) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    [(
+                        header::CONTENT_TYPE,
+                        HeaderValue::from_static(mime::TEXT_PLAIN_UTF_8.as_ref()),
+                    )],
+                    err.to_string(),
+                )
+                    .into_response()
+            }
+        };
+
+        let mut body =
+            BytesMut::with_capacity(ENVELOPE_START.len() + content.len() + ENVELOPE_END.len());
+        body.extend_from_slice(ENVELOPE_START.as_bytes());
+        body.extend_from_slice(&content);
+        body.extend_from_slice(ENVELOPE_END.as_bytes());
+
+        (
+            [(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/soap+xml"),
+            )],
+            body.freeze(),
+        )
+            .into_response()
+    }
+}
@@ -0,0 +1,72 @@
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::ops::{Deref, DerefMut};
+
+/// Text whose whitespace is significant
+///
+/// Serialized as an element with the `xml:space="preserve"` attribute so
+/// consumers don't collapse the whitespace in the text. Deserializes from the
+/// text of an element with or without the attribute, the deserializer trims
+/// the leading and trailing whitespace of text so only the inner whitespace is
+/// kept when reading.
+///
+/// ```
+/// use axum_xml_up::PreserveSpace;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Snippet {
+///     // Written as <code xml:space="preserve">fn main() {}</code>
+///     code: PreserveSpace,
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct PreserveSpace(pub String);
+
+impl Serialize for PreserveSpace {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut element = serializer.serialize_struct("PreserveSpace", 2)?;
+        element.serialize_field("@xml:space", "preserve")?;
+        element.serialize_field("$text", &self.0)?;
+        element.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for PreserveSpace {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        /// Element containing the text
+        #[derive(Deserialize)]
+        struct Element {
+            #[serde(rename = "$text", default)]
+            text: String,
+        }
+
+        Element::deserialize(deserializer).map(|element| Self(element.text))
+    }
+}
+
+impl Deref for PreserveSpace {
+    type Target = String;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for PreserveSpace {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<String> for PreserveSpace {
+    fn from(text: String) -> Self {
+        Self(text)
+    }
+}
@@ -0,0 +1,303 @@
+use crate::config::XmlConfig;
+use crate::rejection::{XmlLimit, XmlRejection};
+use axum_core::body::Body;
+use axum_core::extract::{FromRequest, Request};
+use core::pin::Pin;
+use http_body_util::{BodyDataStream, BodyExt};
+use quick_xml::DeError;
+use serde::de::DeserializeOwned;
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::io::{self, BufRead, Read};
+use tokio_util::io::{StreamReader, SyncIoBridge};
+
+/// XML Extractor that deserializes the body while it is being received
+///
+/// [`Xml`](crate::Xml) reads the whole body into memory before deserializing
+/// it, this extractor instead feeds the body stream to the deserializer so
+/// memory usage stays bounded regardless of the size of the document. The
+/// deserializer runs on the blocking thread pool of the tokio runtime.
+///
+/// The content type, [`XmlConfig::max_body_size`], [`XmlConfig::max_depth`]
+/// and the registered entities are applied the same way as
+/// [`Xml`](crate::Xml), the depth is tracked as the body is read. Options that
+/// need the whole document before it is deserialized, such as the other
+/// document limits, [`XmlConfig::allowed_roots`], [`XmlConfig::repair_malformed`] and the other
+/// options rewriting the document, don't apply. The `charset` parameter of the
+/// content type is ignored, prefer [`Xml`](crate::Xml) for bodies that are not
+/// UTF-8.
+///
+/// Requires the `stream` feature.
+///
+/// ```rust,no_run
+/// use axum_xml_up::StreamingXml;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Import {
+///     record: Vec<String>,
+/// }
+///
+/// async fn import(StreamingXml(import): StreamingXml<Import>) {
+///     // import was deserialized without buffering the whole body
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StreamingXml<T>(pub T);
+
+impl<T, S> FromRequest<S> for StreamingXml<T>
+where
+    T: DeserializeOwned + Send + 'static,
+    S: Send + Sync + 'static,
+{
+    type Rejection = XmlRejection;
+
+    fn from_request<'state, 'future>(
+        mut req: Request<Body>,
+        state: &'state S,
+    ) -> Pin<Box<dyn Future<Output = Result<Self, Self::Rejection>> + Send + 'future>>
+    where
+        'state: 'future,
+        Self: 'future,
+    {
+        Box::pin(async move {
+            let config = XmlConfig::resolve(&mut req, state);
+            let result = deserialize(req, &config).await.map(Self);
+            config.report(result)
+        })
+    }
+}
+
+/// Deserializes the body of `req` as it is read
+async fn deserialize<T>(mut req: Request<Body>, config: &XmlConfig) -> Result<T, XmlRejection>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    crate::check_content_type(&req, config)?;
+
+    if let Some(limit) = config.max_body_size {
+        // Reject bodies that are known to be too large before reading them
+        let size = crate::content_length(&req);
+        if size.is_some_and(|size| size > limit) {
+            return Err(XmlRejection::BodyTooLarge { limit, size });
+        }
+
+        req = crate::limit::limit_body(req, limit);
+    }
+
+    let body = req.into_body().map_err(io::Error::other);
+    let reader = SyncIoBridge::new(StreamReader::new(BodyDataStream::new(body)));
+    let reader = DepthLimit::new(reader, config.max_depth);
+
+    let deserialize_config = config.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        crate::de::from_reader::<T, _>(reader, &deserialize_config)
+    })
+    .await;
+
+    let result = match result {
+        Ok(result) => result,
+        Err(err) => match err.try_into_panic() {
+            Ok(panic) => std::panic::resume_unwind(panic),
+            Err(err) => return Err(DeError::Custom(err.to_string()).into()),
+        },
+    };
+
+    result.map_err(|err| match (err, config.max_body_size) {
+        (DeError::InvalidXml(quick_xml::Error::Io(err)), Some(limit))
+            if crate::limit::exceeded_limit(&*err) =>
+        {
+            XmlRejection::BodyTooLarge { limit, size: None }
+        }
+        (DeError::InvalidXml(quick_xml::Error::Io(err)), _)
+            if err
+                .get_ref()
+                .is_some_and(|err| err.is::<DepthLimitExceeded>()) =>
+        {
+            XmlRejection::LimitExceeded(XmlLimit::Depth)
+        }
+        (err, _) => err.into(),
+    })
+}
+
+/// Error reading a document nested deeper than [`XmlConfig::max_depth`]
+#[derive(Debug)]
+struct DepthLimitExceeded;
+
+impl fmt::Display for DepthLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("document exceeded the configured depth limit")
+    }
+}
+
+impl Error for DepthLimitExceeded {}
+
+/// Markup the [`DepthLimit`] reader is currently inside of
+#[derive(Debug, Clone, Copy)]
+enum Markup {
+    /// Text content between tags
+    Text,
+    /// Just after a `<`
+    Open,
+    /// Start tag, with the quote of the attribute value being read and whether
+    /// the previous byte was a `/`
+    StartTag { quote: Option<u8>, slash: bool },
+    /// End tag
+    EndTag,
+    /// Processing instruction, with whether the previous byte was a `?`
+    Pi { question: bool },
+    /// Just after a `<!`, with whether a `-` was read
+    Bang { dash: bool },
+    /// Comment, with the number of `-` just read
+    Comment { dashes: u8 },
+    /// CDATA section, with the number of `]` just read
+    CData { brackets: u8 },
+    /// DOCTYPE or other declaration, with the number of unclosed `<`, the
+    /// same way quick-xml finds its end
+    Declaration { open: usize },
+}
+
+/// Reader tracking the element depth of the document passing through it,
+/// failing once it exceeds the limit
+///
+/// The deserializer reads the document through the [`BufRead`] methods, the
+/// bytes are scanned when they are first returned by [`BufRead::fill_buf`] so
+/// the error is raised before the deserializer gets to the element.
+struct DepthLimit<R> {
+    inner: R,
+    depth: Depth,
+    /// Number of bytes at the start of the inner buffer already scanned
+    scanned: usize,
+}
+
+impl<R> DepthLimit<R> {
+    fn new(inner: R, limit: Option<usize>) -> Self {
+        Self {
+            inner,
+            depth: Depth {
+                limit,
+                depth: 0,
+                markup: Markup::Text,
+            },
+            scanned: 0,
+        }
+    }
+}
+
+/// Element depth of the bytes scanned so far
+struct Depth {
+    limit: Option<usize>,
+    depth: usize,
+    markup: Markup,
+}
+
+impl Depth {
+    /// Updates the depth with `bytes`, returning whether it exceeded the limit
+    fn scan(&mut self, bytes: &[u8]) -> bool {
+        let Some(limit) = self.limit else {
+            return false;
+        };
+
+        for &byte in bytes {
+            self.markup = match (self.markup, byte) {
+                (Markup::Text, b'<') => Markup::Open,
+                (Markup::Text, _) => Markup::Text,
+                (Markup::Open, b'/') => Markup::EndTag,
+                (Markup::Open, b'?') => Markup::Pi { question: false },
+                (Markup::Open, b'!') => Markup::Bang { dash: false },
+                (Markup::Open, _) => {
+                    self.depth += 1;
+                    if self.depth > limit {
+                        return true;
+                    }
+                    Markup::StartTag {
+                        quote: None,
+                        slash: false,
+                    }
+                }
+                (
+                    Markup::StartTag {
+                        quote: Some(quote), ..
+                    },
+                    _,
+                ) => Markup::StartTag {
+                    quote: (byte != quote).then_some(quote),
+                    slash: false,
+                },
+                (Markup::StartTag { slash, .. }, b'>') => {
+                    if slash {
+                        self.depth -= 1;
+                    }
+                    Markup::Text
+                }
+                (Markup::StartTag { .. }, b'"' | b'\'') => Markup::StartTag {
+                    quote: Some(byte),
+                    slash: false,
+                },
+                (Markup::StartTag { .. }, _) => Markup::StartTag {
+                    quote: None,
+                    slash: byte == b'/',
+                },
+                (Markup::EndTag, b'>') => {
+                    self.depth = self.depth.saturating_sub(1);
+                    Markup::Text
+                }
+                (Markup::EndTag, _) => Markup::EndTag,
+                (Markup::Pi { question: true }, b'>') => Markup::Text,
+                (Markup::Pi { .. }, _) => Markup::Pi {
+                    question: byte == b'?',
+                },
+                (Markup::Bang { dash: false }, b'-') => Markup::Bang { dash: true },
+                (Markup::Bang { dash: true }, b'-') => Markup::Comment { dashes: 0 },
+                (Markup::Bang { dash: false }, b'[') => Markup::CData { brackets: 0 },
+                (Markup::Bang { .. }, b'>') => Markup::Text,
+                (Markup::Bang { .. }, b'<') => Markup::Declaration { open: 2 },
+                (Markup::Bang { .. }, _) => Markup::Declaration { open: 1 },
+                (Markup::Comment { dashes: 2.. }, b'>') => Markup::Text,
+                (Markup::Comment { dashes }, b'-') => Markup::Comment {
+                    dashes: (dashes + 1).min(2),
+                },
+                (Markup::Comment { .. }, _) => Markup::Comment { dashes: 0 },
+                (Markup::CData { brackets: 2.. }, b'>') => Markup::Text,
+                (Markup::CData { brackets }, b']') => Markup::CData {
+                    brackets: (brackets + 1).min(2),
+                },
+                (Markup::CData { .. }, _) => Markup::CData { brackets: 0 },
+                (Markup::Declaration { open }, b'<') => Markup::Declaration { open: open + 1 },
+                (Markup::Declaration { open: 1 }, b'>') => Markup::Text,
+                (Markup::Declaration { open }, b'>') => Markup::Declaration { open: open - 1 },
+                (Markup::Declaration { open }, _) => Markup::Declaration { open },
+            };
+        }
+
+        false
+    }
+}
+
+impl<R: BufRead> Read for DepthLimit<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl<R: BufRead> BufRead for DepthLimit<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let buf = self.inner.fill_buf()?;
+        let unscanned = buf.get(self.scanned..).unwrap_or_default();
+        self.scanned = self.scanned.max(buf.len());
+        if self.depth.scan(unscanned) {
+            return Err(io::Error::other(DepthLimitExceeded));
+        }
+        Ok(buf)
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt);
+        self.scanned = self.scanned.saturating_sub(amt);
+    }
+}
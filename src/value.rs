@@ -0,0 +1,213 @@
+use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::{DeError, Reader, Writer};
+
+use crate::{XmlConfig, XmlRejection};
+
+/// Dynamic XML element tree
+///
+/// Unlike deserializing into a map, the tree keeps attributes and child
+/// elements separate so a document can be round-tripped without attributes
+/// turning into elements (or the other way around).
+///
+/// ```
+/// use axum_xml_up::XmlValue;
+///
+/// let value = XmlValue::from_bytes(br#"<user id="1"><name>Jacob</name></user>"#).unwrap();
+/// assert_eq!(value.attribute("id"), Some("1"));
+/// assert_eq!(value.to_bytes(), br#"<user id="1"><name>Jacob</name></user>"#);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct XmlValue {
+    /// Name of the element
+    pub name: String,
+    /// Attributes of the element in document order
+    pub attributes: Vec<(String, String)>,
+    /// Child nodes of the element in document order
+    pub children: Vec<XmlNode>,
+}
+
+/// Child node of an [`XmlValue`] element
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum XmlNode {
+    /// Child element
+    Element(XmlValue),
+    /// Text content (CDATA sections are also represented as text)
+    Text(String),
+}
+
+impl XmlValue {
+    /// Creates a new element with the provided `name` and no attributes or children
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Parses the root element of the provided document
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DeError> {
+        let mut reader = Reader::from_reader(bytes);
+        // Stack of the currently open elements
+        let mut stack: Vec<XmlValue> = Vec::new();
+        let mut root: Option<XmlValue> = None;
+
+        loop {
+            let element = match reader.read_event()? {
+                Event::Start(start) => {
+                    stack.push(Self::from_start(&reader, &start)?);
+                    continue;
+                }
+                Event::Empty(start) => Self::from_start(&reader, &start)?,
+                Event::End(_) => match stack.pop() {
+                    Some(element) => element,
+                    None => continue,
+                },
+                Event::Text(text) => {
+                    // Text outside of the root element is ignored
+                    if let Some(parent) = stack.last_mut() {
+                        let text = text.unescape()?.into_owned();
+                        parent.children.push(XmlNode::Text(text));
+                    }
+                    continue;
+                }
+                Event::CData(data) => {
+                    if let Some(parent) = stack.last_mut() {
+                        let text = reader.decoder().decode(&data)?.into_owned();
+                        parent.children.push(XmlNode::Text(text));
+                    }
+                    continue;
+                }
+                Event::Eof => break,
+                _ => continue,
+            };
+
+            // Attach the completed element to its parent or make it the root
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(XmlNode::Element(element)),
+                None if root.is_none() => root = Some(element),
+                None => return Err(DeError::Custom("multiple root elements".to_string())),
+            }
+        }
+
+        if !stack.is_empty() {
+            return Err(DeError::UnexpectedEof);
+        }
+
+        root.ok_or(DeError::ExpectedStart)
+    }
+
+    /// Creates an element from its start tag
+    fn from_start(reader: &Reader<&[u8]>, start: &BytesStart) -> Result<Self, DeError> {
+        let decoder = reader.decoder();
+        let name = decoder.decode(start.name().as_ref())?.into_owned();
+        let attributes = start
+            .attributes()
+            .map(|attribute| {
+                let attribute = attribute.map_err(quick_xml::Error::from)?;
+                let key = decoder.decode(attribute.key.as_ref())?.into_owned();
+                let value = attribute.decode_and_unescape_value(reader)?.into_owned();
+                Ok((key, value))
+            })
+            .collect::<Result<_, DeError>>()?;
+
+        Ok(Self {
+            name,
+            attributes,
+            children: Vec::new(),
+        })
+    }
+
+    /// Obtains the value of the attribute named `name`
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Iterates the child elements of this element, skipping text nodes
+    pub fn elements(&self) -> impl Iterator<Item = &XmlValue> {
+        self.children.iter().filter_map(|child| match child {
+            XmlNode::Element(element) => Some(element),
+            XmlNode::Text(_) => None,
+        })
+    }
+
+    /// Creates the canonical form of the provided document, documents that are
+    /// equivalent apart from their formatting have the same canonical form
+    ///
+    /// The document is parsed into an [`XmlValue`] and written back with the
+    /// attributes of each element sorted by name, leading and trailing
+    /// whitespace trimmed from text, and whitespace only text removed. Any
+    /// declaration, comments and processing instructions are dropped.
+    ///
+    /// The document is first checked against the document limits of the
+    /// `config`, such as [`XmlConfig::max_depth`], the same way as the body of
+    /// [`Xml`](crate::Xml).
+    ///
+    /// ```
+    /// use axum_xml_up::{XmlConfig, XmlValue};
+    ///
+    /// let canonical = XmlValue::canonicalize(
+    ///     b"<user name='Jacob' id='1'>\n  <role> admin </role>\n</user>",
+    ///     &XmlConfig::new(),
+    /// );
+    /// assert_eq!(
+    ///     canonical.unwrap(),
+    ///     r#"<user id="1" name="Jacob"><role>admin</role></user>"#
+    /// );
+    /// ```
+    pub fn canonicalize(bytes: &[u8], config: &XmlConfig) -> Result<String, XmlRejection> {
+        crate::scan::scan(bytes, config)?;
+        let mut value = Self::from_bytes(bytes)?;
+        value.normalize();
+        Ok(String::from_utf8_lossy(&value.to_bytes()).into_owned())
+    }
+
+    /// Sorts the attributes by name and trims the text nodes of the tree,
+    /// removing the text nodes that only contain whitespace
+    fn normalize(&mut self) {
+        self.attributes.sort();
+        self.children.retain_mut(|child| match child {
+            XmlNode::Element(element) => {
+                element.normalize();
+                true
+            }
+            XmlNode::Text(text) => {
+                *text = text.trim().to_string();
+                !text.is_empty()
+            }
+        });
+    }
+
+    /// Serializes the element tree back into XML
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Writer::new(Vec::new());
+        self.write(&mut writer)
+            .expect("Writing to a Vec should never fail");
+        writer.into_inner()
+    }
+
+    /// Writes the element and its children to the provided `writer`
+    fn write(&self, writer: &mut Writer<Vec<u8>>) -> Result<(), quick_xml::Error> {
+        let start = BytesStart::new(self.name.as_str()).with_attributes(
+            self.attributes
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.as_str())),
+        );
+
+        // Elements without children are written as empty elements
+        if self.children.is_empty() {
+            return writer.write_event(Event::Empty(start));
+        }
+
+        writer.write_event(Event::Start(start))?;
+        for child in &self.children {
+            match child {
+                XmlNode::Element(element) => element.write(writer)?,
+                XmlNode::Text(text) => writer.write_event(Event::Text(BytesText::new(text)))?,
+            }
+        }
+        writer.write_event(Event::End(BytesEnd::new(self.name.as_str())))
+    }
+}
@@ -0,0 +1,265 @@
+//! Serde helper for enums discriminated by an `xsi:type` attribute
+//!
+//! Schema typed documents represent a field that can hold one of several
+//! derived types as a single element naming the type in an `xsi:type`
+//! attribute, such as `<shape xsi:type="Circle"><radius>1</radius></shape>`.
+//! The [`serialize`] function writes an enum field in this form instead of
+//! using the variant name as the element name. The `xsi` prefix should be
+//! declared on the root element using the [`NAMESPACE`]:
+//!
+//! ```
+//! use axum_xml_up::{xsi_type, Xml, XmlResponse};
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize)]
+//! enum Shape {
+//!     Circle { radius: f64 },
+//!     Square { side: f64 },
+//! }
+//!
+//! #[derive(Serialize)]
+//! struct Drawing {
+//!     #[serde(serialize_with = "xsi_type::serialize")]
+//!     shape: Shape,
+//! }
+//!
+//! async fn get_drawing() -> XmlResponse<Drawing> {
+//!     let drawing = Drawing { shape: Shape::Circle { radius: 1.0 } };
+//!     Xml(drawing).namespace("xsi", xsi_type::NAMESPACE)
+//! }
+//! ```
+//!
+//! Unit variants are written as an empty element and the value of newtype
+//! variants is written as the content of the element. Tuple variants and
+//! values that are not enums can't be serialized.
+
+use serde::ser::{self, Error, Impossible, SerializeStruct};
+use serde::{Serialize, Serializer};
+
+/// Namespace of the `xsi` prefix used by the `xsi:type` attribute
+pub const NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema-instance";
+
+/// Name of the attribute field naming the variant
+const TYPE_ATTRIBUTE: &str = "@xsi:type";
+
+/// Serializes an enum as an element with an `xsi:type` attribute naming the
+/// variant
+pub fn serialize<S, T>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Serialize,
+{
+    value.serialize(VariantSerializer(serializer))
+}
+
+/// Serializer writing the variants of an enum using the wrapped serializer
+struct VariantSerializer<S>(S);
+
+/// Error for values that are not enums with a supported variant
+fn unsupported<E: Error>() -> E {
+    E::custom("xsi:type can only serialize unit, newtype and struct enum variants")
+}
+
+impl<S> Serializer for VariantSerializer<S>
+where
+    S: Serializer,
+{
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = Impossible<S::Ok, S::Error>;
+    type SerializeTuple = Impossible<S::Ok, S::Error>;
+    type SerializeTupleStruct = Impossible<S::Ok, S::Error>;
+    type SerializeTupleVariant = Impossible<S::Ok, S::Error>;
+    type SerializeMap = Impossible<S::Ok, S::Error>;
+    type SerializeStruct = Impossible<S::Ok, S::Error>;
+    type SerializeStructVariant = StructVariant<S::SerializeStruct>;
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        let mut element = self.0.serialize_struct(name, 1)?;
+        element.serialize_field(TYPE_ATTRIBUTE, variant)?;
+        element.end()
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let mut element = self.0.serialize_struct(name, 2)?;
+        element.serialize_field(TYPE_ATTRIBUTE, variant)?;
+        element.serialize_field("$value", value)?;
+        element.end()
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        let mut element = self.0.serialize_struct(name, len + 1)?;
+        element.serialize_field(TYPE_ATTRIBUTE, variant)?;
+        Ok(StructVariant(element))
+    }
+
+    fn serialize_bool(self, _value: bool) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_i8(self, _value: i8) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_i16(self, _value: i16) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_i32(self, _value: i32) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_i64(self, _value: i64) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_u8(self, _value: u8) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_u16(self, _value: u16) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_u32(self, _value: u32) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_u64(self, _value: u64) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_f32(self, _value: f32) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_f64(self, _value: f64) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_char(self, _value: char) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_str(self, _value: &str) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_bytes(self, _value: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        self.0.serialize_none()
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Err(unsupported())
+    }
+}
+
+/// Writes the fields of a struct variant as the fields of the element
+struct StructVariant<S>(S);
+
+impl<S> ser::SerializeStructVariant for StructVariant<S>
+where
+    S: SerializeStruct,
+{
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.0.serialize_field(key, value)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), Self::Error> {
+        self.0.skip_field(key)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.0.end()
+    }
+}
@@ -0,0 +1,895 @@
+use axum::http::{header, StatusCode};
+use axum::{body::to_bytes, response::IntoResponse};
+use axum_xml_up::{AcceptXml, AsXmlResponse, PreserveSpace, Soap, Xml};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// Shared testing output structure for response tests
+#[derive(Debug, Serialize)]
+struct Output {
+    #[serde(rename = "@id")]
+    id: u32,
+    name: String,
+}
+
+/// Serializing a response should write the XML straight into the body bytes
+#[tokio::test]
+async fn serialize_body() {
+    let response = Xml(Output {
+        id: 1,
+        name: "Jürgen".to_string(),
+    })
+    .into_response();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/xml"
+    );
+
+    // Compare the raw bytes as the body is not assumed to be a string
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("Failed to read response body");
+    assert_eq!(
+        &body[..],
+        r#"<Output id="1"><name>Jürgen</name></Output>"#.as_bytes()
+    );
+}
+
+/// Values read from bodies in other encodings should be serialized as UTF-8
+/// bytes with a declaration matching the encoding that is written
+#[cfg(feature = "encoding")]
+#[tokio::test]
+async fn serialize_body_transcoded() {
+    #[derive(Debug, serde::Deserialize, Serialize)]
+    struct Name {
+        #[serde(rename = "$text")]
+        value: String,
+    }
+
+    // "Jürgen €" encoded as windows-1252
+    let input = b"<?xml version=\"1.0\" encoding=\"windows-1252\"?><Name>J\xFCrgen \x80</Name>";
+    let Xml(name) = Xml::<Name>::from_bytes(input).expect("Failed to transcode body");
+    assert_eq!(name.value, "Jürgen €");
+
+    let response = Xml(name).with_declaration(true).into_response();
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("Failed to read response body");
+    assert_eq!(
+        &body[..],
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?><Name>Jürgen €</Name>".as_bytes()
+    );
+}
+
+/// Output structure for namespace prefix tests
+#[derive(Debug, Serialize)]
+struct Prefixed {
+    id: u32,
+    name: String,
+}
+
+/// Fields mapped to a prefix should be emitted with that prefix and the
+/// prefix should be declared on the root
+#[tokio::test]
+async fn serialize_field_prefixes() {
+    let response = Xml(Prefixed {
+        id: 1,
+        name: "Jacob".to_string(),
+    })
+    .namespace("u", "urn:example:user")
+    .field_prefix("name", "u")
+    .into_response();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/xml"
+    );
+
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("Failed to read response body");
+    assert_eq!(
+        &body[..],
+        r#"<Prefixed xmlns:u="urn:example:user"><id>1</id><u:name>Jacob</u:name></Prefixed>"#
+            .as_bytes()
+    );
+}
+
+/// Each of the namespaces should be declared on the root element
+#[tokio::test]
+async fn serialize_namespaces() {
+    let response = Xml(Prefixed {
+        id: 1,
+        name: "Jacob".to_string(),
+    })
+    .namespaces([("a", "urn:example:a"), ("b", "urn:example:b")])
+    .field_prefix("id", "a")
+    .field_prefix("name", "b")
+    .into_response();
+
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("Failed to read response body");
+    assert_eq!(
+        body,
+        r#"<Prefixed xmlns:a="urn:example:a" xmlns:b="urn:example:b"><a:id>1</a:id><b:name>Jacob</b:name></Prefixed>"#
+    );
+}
+
+/// Compressed responses should decompress to the serialized XML
+#[cfg(feature = "compression")]
+#[tokio::test]
+async fn serialize_compressed() {
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    let response = Xml(Output {
+        id: 1,
+        name: "Jacob".to_string(),
+    })
+    .compressed()
+    .into_response();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/xml"
+    );
+    assert_eq!(
+        response.headers().get(header::CONTENT_ENCODING).unwrap(),
+        "gzip"
+    );
+
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("Failed to read response body");
+
+    let mut decompressed = String::new();
+    GzDecoder::new(&body[..])
+        .read_to_string(&mut decompressed)
+        .expect("Failed to decompress response body");
+    assert_eq!(
+        decompressed,
+        r#"<Output id="1"><name>Jacob</name></Output>"#
+    );
+}
+
+/// Responses should only be compressed when the client accepts gzip
+#[cfg(feature = "compression")]
+#[tokio::test]
+async fn serialize_compressed_if_accepted() {
+    use axum::http::{HeaderMap, HeaderValue};
+    use axum_xml_up::AcceptEncoding;
+
+    let data = [
+        (Some("gzip"), true),
+        (Some("br;q=1.0, gzip;q=0.8"), true),
+        (Some("*"), true),
+        (Some("gzip;q=0"), false),
+        (Some("gzip;q=0, *"), false),
+        (Some("br, *;q=0.5"), true),
+        (Some("gzip, *;q=0"), true),
+        (Some("br, deflate"), false),
+        (None, false),
+    ];
+
+    for (accept_encoding, compressed) in data {
+        let mut headers = HeaderMap::new();
+        if let Some(value) = accept_encoding {
+            headers.insert(header::ACCEPT_ENCODING, HeaderValue::from_static(value));
+        }
+
+        let accept = AcceptEncoding::from_headers(&headers);
+        assert_eq!(accept.gzip(), compressed);
+
+        let response = Xml(Output {
+            id: 1,
+            name: "Jacob".to_string(),
+        })
+        .compressed_if_accepted(accept)
+        .into_response();
+
+        assert_eq!(
+            response.headers().get(header::CONTENT_ENCODING).is_some(),
+            compressed
+        );
+        assert_eq!(
+            response.headers().get(header::VARY).unwrap(),
+            "accept-encoding"
+        );
+
+        // Plain responses should contain the serialized XML
+        if !compressed {
+            let body = to_bytes(response.into_body(), usize::MAX)
+                .await
+                .expect("Failed to read response body");
+            assert_eq!(
+                &body[..],
+                r#"<Output id="1"><name>Jacob</name></Output>"#.as_bytes()
+            );
+        }
+    }
+}
+
+/// Optional values should serialize as the inner value, an empty body or
+/// an empty root element
+#[tokio::test]
+async fn serialize_option() {
+    async fn body(response: impl IntoResponse) -> String {
+        let body = to_bytes(response.into_response().into_body(), usize::MAX)
+            .await
+            .expect("Failed to read response body");
+        String::from_utf8(body.to_vec()).expect("Response body should be UTF-8")
+    }
+
+    let value = Some(Output {
+        id: 1,
+        name: "Jacob".to_string(),
+    });
+    assert_eq!(
+        body(Xml(value)).await,
+        r#"<Output id="1"><name>Jacob</name></Output>"#
+    );
+
+    assert_eq!(body(Xml(None::<Output>)).await, "");
+    assert_eq!(
+        body(Xml(None::<Output>).empty_as("Output")).await,
+        "<Output/>"
+    );
+}
+
+/// Output structure with fields declared out of alphabetical order
+#[derive(Debug, Serialize)]
+struct Ordered {
+    zeta: u32,
+    alpha: u32,
+    #[serde(rename = "@id")]
+    id: u32,
+    middle: BTreeMap<String, u32>,
+}
+
+/// Elements should be written in declaration order with attributes on the
+/// start tag and maps in key order
+#[tokio::test]
+async fn serialize_declaration_order() {
+    let middle = BTreeMap::from([("b".to_string(), 2), ("a".to_string(), 1)]);
+    let response = Xml(Ordered {
+        zeta: 1,
+        alpha: 2,
+        id: 3,
+        middle,
+    })
+    .into_response();
+
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("Failed to read response body");
+    assert_eq!(
+        &body[..],
+        r#"<Ordered id="3"><zeta>1</zeta><alpha>2</alpha><middle><a>1</a><b>2</b></middle></Ordered>"#
+            .as_bytes()
+    );
+}
+
+/// Values without an element name should be wrapped in a root element
+#[tokio::test]
+async fn serialize_primitive_root() {
+    async fn body(response: impl IntoResponse) -> (StatusCode, String) {
+        let response = response.into_response();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("Failed to read response body");
+        let body = String::from_utf8(body.to_vec()).expect("Response body should be UTF-8");
+        (status, body)
+    }
+
+    assert_eq!(
+        body(Xml(42u32)).await,
+        (StatusCode::OK, "<value>42</value>".to_string())
+    );
+    assert_eq!(
+        body(Xml("a < b".to_string())).await,
+        (StatusCode::OK, "<value>a &lt; b</value>".to_string())
+    );
+    assert_eq!(
+        body(Xml(42u32).primitive_root("count")).await,
+        (StatusCode::OK, "<count>42</count>".to_string())
+    );
+}
+
+/// Handlers should be able to create responses using the extension trait
+#[tokio::test]
+async fn serialize_into_xml() {
+    async fn handler() -> Xml<Output> {
+        Output {
+            id: 1,
+            name: "Jacob".to_string(),
+        }
+        .into_xml()
+    }
+
+    let body = to_bytes(handler().await.into_response().into_body(), usize::MAX)
+        .await
+        .expect("Failed to read response body");
+    assert_eq!(body, r#"<Output id="1"><name>Jacob</name></Output>"#);
+
+    let response = Output {
+        id: 1,
+        name: "Jacob".to_string(),
+    }
+    .into_xml_pretty()
+    .into_response();
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("Failed to read response body");
+    assert_eq!(body, "<Output id=\"1\">\n  <name>Jacob</name>\n</Output>");
+}
+
+/// Pretty output should use the configured indentation
+#[tokio::test]
+async fn serialize_indent() {
+    let response = Xml(Output {
+        id: 1,
+        name: "Jacob".to_string(),
+    })
+    .indent('\t', 1)
+    .into_response();
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("Failed to read response body");
+    assert_eq!(body, "<Output id=\"1\">\n\t<name>Jacob</name>\n</Output>");
+
+    let response = Xml(Output {
+        id: 1,
+        name: "Jacob".to_string(),
+    })
+    .indent(' ', 4)
+    .into_response();
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("Failed to read response body");
+    assert_eq!(body, "<Output id=\"1\">\n    <name>Jacob</name>\n</Output>");
+}
+
+/// Range requests should respond with the requested part of the body
+#[tokio::test]
+async fn serialize_range() {
+    use axum::http::{HeaderMap, HeaderValue};
+    use axum_xml_up::ByteRange;
+
+    // Full body is `<Output id="1"><name>Jacob</name></Output>`, 42 bytes
+    let data = [
+        (
+            Some("bytes=0-14"),
+            StatusCode::PARTIAL_CONTENT,
+            Some("bytes 0-14/42"),
+            r#"<Output id="1">"#,
+        ),
+        (
+            Some("bytes=33-"),
+            StatusCode::PARTIAL_CONTENT,
+            Some("bytes 33-41/42"),
+            "</Output>",
+        ),
+        (
+            Some("bytes=-9"),
+            StatusCode::PARTIAL_CONTENT,
+            Some("bytes 33-41/42"),
+            "</Output>",
+        ),
+        (
+            Some("bytes=40-100"),
+            StatusCode::PARTIAL_CONTENT,
+            Some("bytes 40-41/42"),
+            "t>",
+        ),
+        (
+            Some("bytes=42-"),
+            StatusCode::RANGE_NOT_SATISFIABLE,
+            Some("bytes */42"),
+            "Requested range not satisfiable",
+        ),
+        (
+            Some("bytes=0-1,4-5"),
+            StatusCode::OK,
+            None,
+            r#"<Output id="1"><name>Jacob</name></Output>"#,
+        ),
+        (
+            None,
+            StatusCode::OK,
+            None,
+            r#"<Output id="1"><name>Jacob</name></Output>"#,
+        ),
+    ];
+
+    for (range, status, content_range, expected) in data {
+        let mut headers = HeaderMap::new();
+        if let Some(value) = range {
+            headers.insert(header::RANGE, HeaderValue::from_static(value));
+        }
+
+        let range = ByteRange::from_headers(&headers);
+        let response = Xml(Output {
+            id: 1,
+            name: "Jacob".to_string(),
+        })
+        .range(range)
+        .into_response();
+
+        assert_eq!(response.status(), status);
+        assert_eq!(
+            response
+                .headers()
+                .get(header::CONTENT_RANGE)
+                .map(|value| value.to_str().unwrap()),
+            content_range
+        );
+        assert_eq!(
+            response.headers().get(header::ACCEPT_RANGES).unwrap(),
+            "bytes"
+        );
+
+        let body = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("Failed to read response body");
+        assert_eq!(body, expected);
+    }
+}
+
+/// Non-ASCII characters should be written as numeric character references
+#[tokio::test]
+async fn serialize_ascii_only() {
+    let response = Xml(Output {
+        id: 1,
+        name: "Jürgen Zoë 日本".to_string(),
+    })
+    .ascii_only()
+    .into_response();
+
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("Failed to read response body");
+    assert_eq!(
+        body,
+        r#"<Output id="1"><name>J&#xFC;rgen Zo&#xEB; &#x65E5;&#x672C;</name></Output>"#
+    );
+}
+
+/// Bodies that fail validation should be replaced with an error response
+#[tokio::test]
+async fn serialize_validate_with() {
+    use axum_xml_up::XmlValue;
+
+    /// Validator requiring the root element to have a `name` child
+    fn require_name(body: &[u8]) -> Result<(), String> {
+        let root = XmlValue::from_bytes(body).map_err(|err| err.to_string())?;
+        if root.elements().any(|element| element.name == "name") {
+            return Ok(());
+        }
+        Err(format!("`{}` is missing the name element", root.name))
+    }
+
+    let response = Xml(Output {
+        id: 1,
+        name: "Jacob".to_string(),
+    })
+    .validate_with(require_name)
+    .into_response();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = Xml(Ordered {
+        zeta: 1,
+        alpha: 2,
+        id: 3,
+        middle: BTreeMap::new(),
+    })
+    .validate_with(require_name)
+    .into_response();
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("Failed to read response body");
+    assert_eq!(
+        body,
+        "Response failed validation: `Ordered` is missing the name element"
+    );
+}
+
+/// The content security policy headers should only be set when enabled
+#[tokio::test]
+async fn serialize_content_security_policy() {
+    let output = || Output {
+        id: 1,
+        name: "Jacob".to_string(),
+    };
+
+    let response = Xml(output()).content_security_policy().into_response();
+    assert_eq!(
+        response
+            .headers()
+            .get(header::CONTENT_SECURITY_POLICY)
+            .unwrap(),
+        "default-src 'none'; script-src 'self'; style-src 'self'; img-src 'self'"
+    );
+    assert_eq!(
+        response
+            .headers()
+            .get(header::X_CONTENT_TYPE_OPTIONS)
+            .unwrap(),
+        "nosniff"
+    );
+
+    let response = Xml(output()).into_response();
+    assert!(response
+        .headers()
+        .get(header::CONTENT_SECURITY_POLICY)
+        .is_none());
+}
+
+/// The XML declaration should start the body when enabled
+#[tokio::test]
+async fn serialize_with_declaration() {
+    let response = Xml(Output {
+        id: 1,
+        name: "Jürgen".to_string(),
+    })
+    .with_declaration(true)
+    .into_response();
+
+    assert_eq!(
+        response.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/xml"
+    );
+
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("Failed to read response body");
+    assert_eq!(
+        &body[..],
+        r#"<?xml version="1.0" encoding="UTF-8"?><Output id="1"><name>Jürgen</name></Output>"#
+            .as_bytes()
+    );
+}
+
+/// Output structure with text whose whitespace is significant
+#[derive(Debug, Serialize)]
+struct Snippet {
+    title: String,
+    code: PreserveSpace,
+}
+
+/// Preserved text should be written with the `xml:space` attribute
+#[tokio::test]
+async fn serialize_preserve_space() {
+    let response = Xml(Snippet {
+        title: "Example".to_string(),
+        code: PreserveSpace("  indented\n    more  ".to_string()),
+    })
+    .into_response();
+
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("Failed to read response body");
+    assert_eq!(
+        body,
+        "<Snippet><title>Example</title><code xml:space=\"preserve\">  indented\n    more  </code></Snippet>"
+    );
+}
+
+/// The status should replace `200 OK` while keeping the XML content type
+#[tokio::test]
+async fn serialize_with_status() {
+    let response = Xml(Output {
+        id: 1,
+        name: "Jacob".to_string(),
+    })
+    .with_status(StatusCode::CREATED)
+    .into_response();
+
+    assert_eq!(response.status(), StatusCode::CREATED);
+    assert_eq!(
+        response.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/xml"
+    );
+
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("Failed to read response body");
+    assert_eq!(body, r#"<Output id="1"><name>Jacob</name></Output>"#);
+}
+
+/// SOAP responses should wrap the value in a SOAP 1.2 envelope
+#[tokio::test]
+async fn serialize_soap_envelope() {
+    let response = Soap::response(Output {
+        id: 1,
+        name: "Jacob".to_string(),
+    })
+    .into_response();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/soap+xml"
+    );
+
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("Failed to read response body");
+    assert_eq!(
+        body,
+        concat!(
+            r#"<env:Envelope xmlns:env="http://www.w3.org/2003/05/soap-envelope">"#,
+            r#"<env:Body><Output id="1"><name>Jacob</name></Output></env:Body>"#,
+            "</env:Envelope>"
+        )
+    );
+}
+
+/// The root element should use the provided name instead of the type name
+#[tokio::test]
+async fn serialize_root_name() {
+    let response = Xml(Output {
+        id: 1,
+        name: "Jacob".to_string(),
+    })
+    .root_name("user")
+    .into_response();
+
+    assert_eq!(
+        response.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/xml"
+    );
+
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("Failed to read response body");
+    assert_eq!(body, r#"<user id="1"><name>Jacob</name></user>"#);
+}
+
+/// XML should only be chosen when one of the accepted media types covers it
+#[test]
+fn accept_xml() {
+    use axum::http::{HeaderMap, HeaderValue};
+
+    let data = [
+        (
+            Some("application/json;q=0.5, application/xml, text/html"),
+            true,
+        ),
+        (Some("text/html, application/soap+xml;q=0.9"), true),
+        (Some("application/json, */*;q=0.1"), true),
+        (Some("text/*"), true),
+        (Some("application/json, application/xml;q=0"), false),
+        (Some("application/json, text/html"), false),
+        (Some("application/xml;q=0, */*"), false),
+        (Some("text/*;q=0, text/xml"), true),
+        (Some("*/*;q=0, application/*"), true),
+        (None, true),
+    ];
+
+    for (accept, accepted) in data {
+        let mut headers = HeaderMap::new();
+        if let Some(value) = accept {
+            headers.insert(header::ACCEPT, HeaderValue::from_static(value));
+        }
+
+        assert_eq!(AcceptXml::from_headers(&headers).accepted(), accepted);
+    }
+}
+
+/// Responses should be serialized in the format preferred by the `Accept` header
+#[cfg(feature = "json")]
+#[tokio::test]
+async fn negotiate_format() {
+    use axum::http::{HeaderMap, HeaderValue};
+    use axum_xml_up::Negotiate;
+
+    let data = [
+        (
+            Some("application/json, application/xml;q=0.9"),
+            "application/json",
+            r#"{"@id":1,"name":"Jacob"}"#,
+        ),
+        (
+            Some("application/json;q=0.5, text/xml"),
+            "application/xml",
+            r#"<Output id="1"><name>Jacob</name></Output>"#,
+        ),
+        (
+            Some("application/json, */*;q=0.1"),
+            "application/json",
+            r#"{"@id":1,"name":"Jacob"}"#,
+        ),
+        (
+            Some("*/*"),
+            "application/xml",
+            r#"<Output id="1"><name>Jacob</name></Output>"#,
+        ),
+        (
+            None,
+            "application/xml",
+            r#"<Output id="1"><name>Jacob</name></Output>"#,
+        ),
+    ];
+
+    for (accept, content_type, expected) in data {
+        let mut headers = HeaderMap::new();
+        if let Some(value) = accept {
+            headers.insert(header::ACCEPT, HeaderValue::from_static(value));
+        }
+
+        let response = Negotiate::from_headers(&headers)
+            .respond(Output {
+                id: 1,
+                name: "Jacob".to_string(),
+            })
+            .into_response();
+
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            content_type
+        );
+        assert_eq!(response.headers().get(header::VARY).unwrap(), "accept");
+
+        let body = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("Failed to read response body");
+        assert_eq!(body, expected);
+    }
+}
+
+/// Output with a collection field that may be empty
+#[derive(Debug, Serialize)]
+struct Basket {
+    #[serde(rename = "@id")]
+    id: u32,
+    items: Vec<String>,
+}
+
+/// Empty collections should be omitted unless an empty element is requested
+#[tokio::test]
+async fn serialize_empty_collection() {
+    let data = [
+        (false, r#"<Basket id="1"/>"#),
+        (true, r#"<Basket id="1"><items/></Basket>"#),
+    ];
+
+    for (empty_element, expected) in data {
+        let response = Xml(Basket {
+            id: 1,
+            items: Vec::new(),
+        });
+        let response = if empty_element {
+            response.empty_collection("items").into_response()
+        } else {
+            response.into_response()
+        };
+
+        let body = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("Failed to read response body");
+        assert_eq!(body, expected);
+    }
+}
+
+/// The body should end with a newline only when enabled
+#[tokio::test]
+async fn serialize_trailing_newline() {
+    let data = [
+        (false, "<Output id=\"1\"><name>Jacob</name></Output>"),
+        (true, "<Output id=\"1\"><name>Jacob</name></Output>\n"),
+    ];
+
+    for (newline, expected) in data {
+        let response = Xml(Output {
+            id: 1,
+            name: "Jacob".to_string(),
+        })
+        .trailing_newline(newline)
+        .into_response();
+
+        let body = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("Failed to read response body");
+        assert_eq!(body, expected);
+    }
+}
+
+/// The content type should include the charset only when enabled
+#[tokio::test]
+async fn serialize_with_charset() {
+    let data = [
+        (false, "application/xml"),
+        (true, "application/xml; charset=utf-8"),
+    ];
+
+    for (charset, expected) in data {
+        let response = Xml(Output {
+            id: 1,
+            name: "Jürgen".to_string(),
+        })
+        .with_charset(charset)
+        .into_response();
+
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            expected
+        );
+
+        let body = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("Failed to read response body");
+        assert_eq!(body, r#"<Output id="1"><name>Jürgen</name></Output>"#);
+    }
+}
+
+/// Shapes discriminated by their `xsi:type`
+#[derive(Debug, Serialize)]
+enum Shape {
+    Circle { radius: u32 },
+    Square { side: u32 },
+}
+
+/// Output with enum fields written using `xsi:type`
+#[derive(Debug, Serialize)]
+struct Drawing {
+    #[serde(serialize_with = "axum_xml_up::xsi_type::serialize")]
+    primary: Shape,
+    #[serde(serialize_with = "axum_xml_up::xsi_type::serialize")]
+    secondary: Shape,
+}
+
+/// Enum variants should be named by an `xsi:type` attribute on the field element
+#[tokio::test]
+async fn serialize_xsi_type() {
+    let response = Xml(Drawing {
+        primary: Shape::Circle { radius: 1 },
+        secondary: Shape::Square { side: 2 },
+    })
+    .namespace("xsi", axum_xml_up::xsi_type::NAMESPACE)
+    .into_response();
+
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("Failed to read response body");
+    assert_eq!(
+        body,
+        concat!(
+            r#"<Drawing xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">"#,
+            r#"<primary xsi:type="Circle"><radius>1</radius></primary>"#,
+            r#"<secondary xsi:type="Square"><side>2</side></secondary>"#,
+            "</Drawing>"
+        )
+    );
+}
+
+/// The expiry time should be written to the `Expires` header as an HTTP date
+#[tokio::test]
+async fn serialize_expires_at() {
+    let output = || Output {
+        id: 1,
+        name: "Jacob".to_string(),
+    };
+
+    let expires = UNIX_EPOCH + Duration::from_secs(784_111_777);
+    let response = Xml(output()).expires_at(expires).into_response();
+    assert_eq!(
+        response.headers().get(header::EXPIRES).unwrap(),
+        "Sun, 06 Nov 1994 08:49:37 GMT"
+    );
+
+    let leap_day = UNIX_EPOCH + Duration::from_secs(1_709_164_800);
+    let response = Xml(output()).expires_at(leap_day).into_response();
+    assert_eq!(
+        response.headers().get(header::EXPIRES).unwrap(),
+        "Thu, 29 Feb 2024 00:00:00 GMT"
+    );
+}
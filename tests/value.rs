@@ -0,0 +1,74 @@
+use axum_xml_up::{XmlConfig, XmlLimit, XmlNode, XmlRejection, XmlValue};
+
+/// Round-tripping a document should keep attributes as attributes and child
+/// elements as child elements
+#[test]
+fn round_trip_attributes_and_elements() {
+    let input = r#"<user id="1" role="admin &amp; owner"><name>Jacob</name><tags><tag kind="a"/><tag>b</tag></tags></user>"#;
+
+    let value = XmlValue::from_bytes(input.as_bytes()).expect("Failed to parse document");
+
+    // Ensure the attributes were parsed as attributes
+    assert_eq!(value.name, "user");
+    assert_eq!(value.attribute("id"), Some("1"));
+    assert_eq!(value.attribute("role"), Some("admin & owner"));
+    assert_eq!(value.attribute("name"), None);
+
+    // Ensure the children were parsed as elements
+    let names: Vec<&str> = value.elements().map(|child| child.name.as_str()).collect();
+    assert_eq!(names, ["name", "tags"]);
+    assert_eq!(
+        value.elements().next().unwrap().children,
+        [XmlNode::Text("Jacob".to_string())]
+    );
+
+    // Ensure the serialized output matches the input
+    let output = value.to_bytes();
+    assert_eq!(String::from_utf8(output).unwrap(), input);
+}
+
+/// Equivalent documents with different formatting should have the same
+/// canonical form
+#[test]
+fn canonicalize_equivalent_documents() {
+    let first = r#"<user id="1" role="admin"><name>Jacob</name><tags><tag/></tags></user>"#;
+    let second = r#"<?xml version="1.0"?>
+<user role='admin'   id="1">
+    <!-- The name of the user -->
+    <name> Jacob </name>
+    <tags>
+        <tag></tag>
+    </tags>
+</user>
+"#;
+
+    let config = XmlConfig::new();
+    let first =
+        XmlValue::canonicalize(first.as_bytes(), &config).expect("Failed to canonicalize first");
+    let second =
+        XmlValue::canonicalize(second.as_bytes(), &config).expect("Failed to canonicalize second");
+    assert_eq!(first, second);
+    assert_eq!(
+        first,
+        r#"<user id="1" role="admin"><name>Jacob</name><tags><tag/></tags></user>"#
+    );
+}
+
+/// Documents nested deeper than the depth limit should be rejected before the
+/// tree is built
+#[test]
+fn canonicalize_max_depth() {
+    let input = format!("{}{}", "<a>".repeat(200_000), "</a>".repeat(200_000));
+
+    let rejection = XmlValue::canonicalize(input.as_bytes(), &XmlConfig::new())
+        .expect_err("Deeply nested document should be rejected");
+    assert!(matches!(
+        rejection,
+        XmlRejection::LimitExceeded(XmlLimit::Depth)
+    ));
+
+    let config = XmlConfig::new().max_depth(Some(2));
+    XmlValue::canonicalize(b"<a><b/></a>", &config).expect("Document within the limit");
+    XmlValue::canonicalize(b"<a><b><c/></b></a>", &config)
+        .expect_err("Document deeper than the limit should be rejected");
+}
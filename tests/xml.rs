@@ -1,9 +1,19 @@
-use std::net::Ipv4Addr;
+use std::{borrow::Cow, net::Ipv4Addr, time::Duration};
 
-use axum::{routing::post, Router};
-use axum_xml_up::Xml;
+use axum::{
+    body::Body,
+    extract::{DefaultBodyLimit, FromRequest, Request},
+    middleware::{self, Next},
+    routing::post,
+    Extension, Router,
+};
+use axum_xml_up::{
+    IntoOwned, OptionalXml, PartialXml, RawXml, RejectionFormat, TimedXml, Xml, XmlConfig,
+    XmlErrorKind, XmlEvents, XmlItems, XmlLimit, XmlMeta, XmlRejected, XmlRejection,
+    XmlWithAttributes, XmlWithHeaders, XmlWithMeta,
+};
 use reqwest::{header, RequestBuilder, StatusCode};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tokio::{net::TcpListener, task::AbortHandle};
 
 /// Testing harness for starting a server and
@@ -113,6 +123,33 @@ async fn require_content_type() {
     )
 }
 
+/// Bodies without an XML content type should be parsed when the content type
+/// isn't required
+#[tokio::test]
+async fn lenient_content_type() {
+    let config = XmlConfig::new().require_content_type(false);
+    let router = Router::new()
+        .route("/", post(|Xml(input): Xml<Input>| async { input.foo }))
+        .layer(Extension(config));
+    let harness = TestHarness::new(router).await;
+
+    let data = [
+        (None, r#"<Input foo="bar"/>"#, StatusCode::OK),
+        (Some("text/plain"), r#"<Input foo="bar"/>"#, StatusCode::OK),
+        (None, "foo=bar", StatusCode::BAD_REQUEST),
+    ];
+
+    for (content_type, body, expected) in data {
+        let mut request = harness.post("/").body(body);
+        if let Some(content_type) = content_type {
+            request = request.header(header::CONTENT_TYPE, content_type);
+        }
+
+        let response = request.send().await.expect("Failed to send request");
+        assert_eq!(response.status(), expected, "{content_type:?} {body:?}");
+    }
+}
+
 /// Tests a collection of valid and invalid content types to ensure the server
 /// accepts all the valid types and rejects the invalid types
 #[tokio::test]
@@ -165,3 +202,2925 @@ async fn valid_content_types() {
         test_valid_content_type(&harness, content_type, valid).await;
     }
 }
+
+/// Shared testing input structure for handlers reading element text
+#[derive(Debug, Deserialize)]
+struct TextInput {
+    name: String,
+}
+
+/// Custom entities registered on the config should be expanded while
+/// entities declared by the document itself are rejected
+#[tokio::test]
+async fn custom_entities() {
+    let config = XmlConfig::new().entity("company", "Acme Inc.");
+    let router = Router::new()
+        .route("/", post(|Xml(input): Xml<TextInput>| async { input.name }))
+        .layer(Extension(config));
+    let harness = TestHarness::new(router).await;
+
+    let response = harness
+        .post("/")
+        .header(header::CONTENT_TYPE, "application/xml")
+        .body(r#"<TextInput><name>&company; &amp; Co</name></TextInput>"#)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    let status = response.status();
+    assert_eq!(status, StatusCode::OK);
+
+    // Ensure the entity was expanded
+    let body = response.text().await.expect("Failed to get response text");
+    assert_eq!(body, "Acme Inc. & Co");
+
+    let response = harness
+        .post("/")
+        .header(header::CONTENT_TYPE, "application/xml")
+        .body(
+            r#"<!DOCTYPE TextInput [<!ENTITY declared "value">]><TextInput><name>&declared;</name></TextInput>"#,
+        )
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    // Entities declared by the document must not be expanded
+    let status = response.status();
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+/// Response body where the variant is chosen by which child element is present
+#[derive(Debug, Deserialize)]
+struct ResponseBody {
+    #[serde(rename = "$value")]
+    result: ResponseResult,
+}
+
+/// Variants of [`ResponseBody`] keyed by their element name
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ResponseResult {
+    Success { id: u32 },
+    Error { message: String },
+}
+
+/// Enums should be selected based on the name of the child element present
+#[tokio::test]
+async fn element_discriminated_enum() {
+    let router = Router::new().route(
+        "/",
+        post(|Xml(input): Xml<ResponseBody>| async move {
+            match input.result {
+                ResponseResult::Success { id } => format!("success {id}"),
+                ResponseResult::Error { message } => format!("error {message}"),
+            }
+        }),
+    );
+    let harness = TestHarness::new(router).await;
+
+    let data = [
+        (
+            "<response><success><id>5</id></success></response>",
+            "success 5",
+        ),
+        (
+            "<response><error><message>Failed</message></error></response>",
+            "error Failed",
+        ),
+    ];
+
+    for (input, expected) in data {
+        let response = harness
+            .post("/")
+            .header(header::CONTENT_TYPE, "application/xml")
+            .body(input)
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        let status = response.status();
+        assert_eq!(status, StatusCode::OK);
+
+        // Ensure the correct variant was chosen
+        let body = response.text().await.expect("Failed to get response text");
+        assert_eq!(body, expected);
+    }
+}
+
+/// Bodies without a root element should be rejected before deserialization
+#[tokio::test]
+async fn require_root_element() {
+    let router = Router::new().route("/", post(|Xml(_): Xml<Input>| async {}));
+    let harness = TestHarness::new(router).await;
+
+    let data = [r#"<?xml version="1.0" encoding="UTF-8"?>"#, "  \n  ", ""];
+
+    for input in data {
+        let response = harness
+            .post("/")
+            .header(header::CONTENT_TYPE, "application/xml")
+            .body(input)
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        // Ensure the correct response status
+        let status = response.status();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // Ensure the correct error response
+        let body = response.text().await.expect("Failed to get response text");
+        assert_eq!(body, "Expected the request body to contain a root element");
+    }
+}
+
+/// Bodies containing text without any elements should be rejected as not
+/// being a document
+#[tokio::test]
+async fn not_a_document() {
+    let router = Router::new().route("/", post(|Xml(_): Xml<Input>| async {}));
+    let harness = TestHarness::new(router).await;
+
+    let response = harness
+        .post("/")
+        .header(header::CONTENT_TYPE, "application/xml")
+        .body("just text")
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    let status = response.status();
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let body = response.text().await.expect("Failed to get response text");
+    assert_eq!(
+        body,
+        "Expected the request body to be an XML document, found text without any elements"
+    );
+
+    let err = Xml::<Input>::from_bytes(b"<?xml version=\"1.0\"?>just text")
+        .expect_err("Text after the declaration should be rejected");
+    assert!(matches!(err, XmlRejection::NotADocument));
+}
+
+/// Input structure with two lists that can be overlapped
+#[cfg(feature = "overlapped-lists")]
+#[derive(Debug, Deserialize)]
+struct OverlappedInput {
+    item: Vec<u32>,
+    other: Vec<u32>,
+}
+
+/// The configured event buffer size should be used by the deserializer
+#[cfg(feature = "overlapped-lists")]
+#[tokio::test]
+async fn event_buffer_size() {
+    use std::num::NonZeroUsize;
+
+    async fn test_buffer_size(size: usize, expected: StatusCode) {
+        let config = XmlConfig::new().event_buffer_size(NonZeroUsize::new(size));
+        let router = Router::new()
+            .route(
+                "/",
+                post(|Xml(input): Xml<OverlappedInput>| async move {
+                    format!("{:?} {:?}", input.item, input.other)
+                }),
+            )
+            .layer(Extension(config));
+        let harness = TestHarness::new(router).await;
+
+        let response = harness
+            .post("/")
+            .header(header::CONTENT_TYPE, "application/xml")
+            .body("<list><item>1</item><other>2</other><other>3</other><item>4</item></list>")
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        let status = response.status();
+        assert_eq!(status, expected);
+    }
+
+    // Buffering the two `other` elements requires 6 events
+    test_buffer_size(6, StatusCode::OK).await;
+    test_buffer_size(5, StatusCode::UNPROCESSABLE_ENTITY).await;
+}
+
+/// Both the deserialized value and the request headers should be available
+#[tokio::test]
+async fn deserialize_with_headers() {
+    let router = Router::new().route(
+        "/",
+        post(
+            |XmlWithHeaders { value, headers }: XmlWithHeaders<Input>| async move {
+                let tenant = headers
+                    .get("x-tenant")
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or_default()
+                    .to_string();
+                format!("{} {tenant}", value.foo)
+            },
+        ),
+    );
+    let harness = TestHarness::new(router).await;
+    let response = harness
+        .post("/")
+        .header(header::CONTENT_TYPE, "application/xml")
+        .header("x-tenant", "acme")
+        .body(r#"<Input foo="bar"/>"#)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    let status = response.status();
+    assert_eq!(status, StatusCode::OK);
+
+    // Ensure both the value and header were provided
+    let body = response.text().await.expect("Failed to get response text");
+    assert_eq!(body, "bar acme");
+}
+
+/// Root level sequences should deserialize from both a single element
+/// and multiple elements
+#[tokio::test]
+async fn one_or_many_elements() {
+    let router = Router::new().route(
+        "/",
+        post(|Xml(input): Xml<Vec<Input>>| async move {
+            input
+                .into_iter()
+                .map(|input| input.foo)
+                .collect::<Vec<_>>()
+                .join(",")
+        }),
+    );
+    let harness = TestHarness::new(router).await;
+
+    let data = [
+        (r#"<Input foo="a"/>"#, "a"),
+        (
+            r#"<Input foo="a"/><Input foo="b"/><Input foo="c"/>"#,
+            "a,b,c",
+        ),
+    ];
+
+    for (input, expected) in data {
+        let response = harness
+            .post("/")
+            .header(header::CONTENT_TYPE, "application/xml")
+            .body(input)
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        let status = response.status();
+        assert_eq!(status, StatusCode::OK);
+
+        // Ensure every element was collected
+        let body = response.text().await.expect("Failed to get response text");
+        assert_eq!(body, expected);
+    }
+}
+
+/// Body read failures should use the crate error format when normalized
+#[tokio::test]
+async fn normalize_body_errors() {
+    async fn test_normalize(normalize: bool, expected: &str) {
+        let config = XmlConfig::new().normalize_body_errors(normalize);
+        let router = Router::new()
+            .route("/", post(|Xml(_): Xml<Input>| async {}))
+            .layer(Extension(config))
+            .layer(DefaultBodyLimit::max(8));
+        let harness = TestHarness::new(router).await;
+
+        let response = harness
+            .post("/")
+            .header(header::CONTENT_TYPE, "application/xml")
+            .body(r#"<Input foo="bar"/>"#)
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        // Status of the underlying rejection should be kept
+        let status = response.status();
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+
+        let body = response.text().await.expect("Failed to get response text");
+        assert_eq!(body, expected);
+    }
+
+    test_normalize(true, "Failed to read the request body").await;
+    test_normalize(
+        false,
+        "Failed to buffer the request body: length limit exceeded",
+    )
+    .await;
+}
+
+/// Input structure generic over a lifetime that borrows while deserializing
+#[derive(Debug, Deserialize)]
+struct BorrowedInput<'a> {
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+}
+
+impl IntoOwned for BorrowedInput<'_> {
+    type Owned = BorrowedInput<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        BorrowedInput {
+            name: IntoOwned::into_owned(self.name),
+        }
+    }
+}
+
+/// Input structure generic over a lifetime that doesn't borrow
+#[derive(Debug, Deserialize)]
+struct LifetimeInput<'a> {
+    name: Cow<'a, str>,
+}
+
+/// Types generic over a lifetime should be usable in their owned form
+#[tokio::test]
+async fn deserialize_owned_lifetime() {
+    // Borrowed values should be convertible into the owned form
+    let document = String::from("<BorrowedInput><name>Jacob</name></BorrowedInput>");
+    let borrowed: BorrowedInput =
+        quick_xml::de::from_str(&document).expect("Failed to deserialize document");
+    assert!(matches!(borrowed.name, Cow::Borrowed(_)));
+
+    let Xml(owned): Xml<BorrowedInput<'static>> = Xml(borrowed).into_owned();
+    drop(document);
+    assert_eq!(owned.name, "Jacob");
+
+    // Types that don't borrow can be extracted with a static lifetime
+    let router = Router::new().route(
+        "/",
+        post(|Xml(input): Xml<LifetimeInput<'static>>| async move { input.name }),
+    );
+    let harness = TestHarness::new(router).await;
+    let response = harness
+        .post("/")
+        .header(header::CONTENT_TYPE, "application/xml")
+        .body("<LifetimeInput><name>Jacob</name></LifetimeInput>")
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    let status = response.status();
+    assert_eq!(status, StatusCode::OK);
+
+    let body = response.text().await.expect("Failed to get response text");
+    assert_eq!(body, "Jacob");
+}
+
+/// Extracts `T` from a request with the provided `content_type` and `body`
+/// without running a server
+async fn extract<T>(content_type: &str, body: &'static str) -> Result<Xml<T>, XmlRejection>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let request = Request::builder()
+        .header(axum::http::header::CONTENT_TYPE, content_type)
+        .body(Body::from(body))
+        .expect("Failed to create request");
+    Xml::<T>::from_request(request, &()).await
+}
+
+/// Input structure with a typed field for error classification
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct TypedInput {
+    count: u32,
+}
+
+/// Rejections should be classified by the kind of failure
+#[tokio::test]
+async fn rejection_kind() {
+    let data = [
+        (
+            "application/xml",
+            "<TypedInput><count>1</count>",
+            XmlErrorKind::Syntax,
+        ),
+        (
+            "application/xml",
+            "<TypedInput><count>1</wrong></TypedInput>",
+            XmlErrorKind::Syntax,
+        ),
+        (
+            "application/xml",
+            "<TypedInput><count>many</count></TypedInput>",
+            XmlErrorKind::TypeMismatch,
+        ),
+        (
+            "application/xml",
+            "<TypedInput></TypedInput>",
+            XmlErrorKind::MissingField,
+        ),
+        (
+            "application/json",
+            "<TypedInput><count>1</count></TypedInput>",
+            XmlErrorKind::ContentType,
+        ),
+    ];
+
+    for (content_type, body, kind) in data {
+        let rejection = extract::<TypedInput>(content_type, body)
+            .await
+            .expect_err("Extraction should fail");
+        assert_eq!(rejection.kind(), kind, "{body}: {rejection:?}");
+    }
+}
+
+/// Content types with stray whitespace or odd casing should still be accepted
+#[tokio::test]
+async fn tolerant_content_types() {
+    let data = [
+        "application/xml ",
+        " application/xml",
+        "Application/XML",
+        "text/xml; charset=utf-8 ",
+    ];
+
+    for content_type in data {
+        let Xml(input) = extract::<Input>(content_type, r#"<Input foo="bar"/>"#)
+            .await
+            .unwrap_or_else(|err| panic!("{content_type:?} should be accepted: {err}"));
+        assert_eq!(input.foo, "bar");
+    }
+}
+
+/// Input structure built from the top level elements of a fragment
+#[derive(Debug, Deserialize)]
+struct FragmentInput {
+    name: String,
+    email: String,
+}
+
+/// Fragments with multiple top level elements should deserialize into a
+/// struct when wrapping is enabled
+#[tokio::test]
+async fn wrap_fragments() {
+    let config = XmlConfig::new().wrap_fragments(true);
+    let router = Router::new()
+        .route(
+            "/",
+            post(|Xml(input): Xml<FragmentInput>| async move {
+                format!("{} {}", input.name, input.email)
+            }),
+        )
+        .layer(Extension(config));
+    let harness = TestHarness::new(router).await;
+
+    let data = [
+        "<name>Jacob</name><email>jacob@example.com</email>",
+        r#"<?xml version="1.0"?>
+        <name>Jacob</name>
+        <email>jacob@example.com</email>"#,
+    ];
+
+    for input in data {
+        let response = harness
+            .post("/")
+            .header(header::CONTENT_TYPE, "application/xml")
+            .body(input)
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        let status = response.status();
+        assert_eq!(status, StatusCode::OK);
+
+        // Ensure the top level elements were used as the fields
+        let body = response.text().await.expect("Failed to get response text");
+        assert_eq!(body, "Jacob jacob@example.com");
+    }
+}
+
+/// Markup heavy documents should be rejected when the ratio limit is set
+/// while normal documents are still accepted
+#[tokio::test]
+async fn max_markup_ratio() {
+    let config = XmlConfig::new().max_markup_ratio(1.0, 16);
+    let router = Router::new()
+        .route("/", post(|Xml(input): Xml<TextInput>| async { input.name }))
+        .layer(Extension(config));
+    let harness = TestHarness::new(router).await;
+
+    let normal = "<TextInput><name>Jacob</name></TextInput>".to_string();
+    let pathological = format!(
+        "<TextInput><name>a</name>{}</TextInput>",
+        "<x/>".repeat(1000)
+    );
+
+    let response = harness
+        .post("/")
+        .header(header::CONTENT_TYPE, "application/xml")
+        .body(normal)
+        .send()
+        .await
+        .expect("Failed to send request");
+    let status = response.status();
+    assert_eq!(status, StatusCode::OK);
+
+    let response = harness
+        .post("/")
+        .header(header::CONTENT_TYPE, "application/xml")
+        .body(pathological)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    // Ensure the correct response status
+    let status = response.status();
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Ensure the correct error response
+    let body = response.text().await.expect("Failed to get response text");
+    assert_eq!(body, "Request body exceeded the markup to text ratio limit");
+}
+
+/// XML and form encoded bodies should deserialize into the same value
+#[cfg(feature = "form")]
+#[tokio::test]
+async fn xml_or_form() {
+    use axum_xml_up::XmlOrForm;
+
+    let router = Router::new().route(
+        "/",
+        post(|XmlOrForm(input): XmlOrForm<TextInput>| async { input.name }),
+    );
+    let harness = TestHarness::new(router).await;
+
+    let data = [
+        (
+            "application/xml",
+            "<TextInput><name>Jacob</name></TextInput>",
+        ),
+        ("application/x-www-form-urlencoded", "name=Jacob"),
+    ];
+
+    for (content_type, input) in data {
+        let response = harness
+            .post("/")
+            .header(header::CONTENT_TYPE, content_type)
+            .body(input)
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        let status = response.status();
+        assert_eq!(status, StatusCode::OK);
+
+        // Ensure both formats produce the same value
+        let body = response.text().await.expect("Failed to get response text");
+        assert_eq!(body, "Jacob");
+    }
+}
+
+/// The XML content type of form fallbacks should come from the config, with
+/// form bodies read using the same body options
+#[cfg(feature = "form")]
+#[tokio::test]
+async fn xml_or_form_config() {
+    use axum_xml_up::XmlOrForm;
+
+    let config = XmlConfig::new()
+        .accepted_content_types(["application/vnd.acme+xml"])
+        .max_body_size(Some(24));
+    let router = Router::new()
+        .route(
+            "/",
+            post(|XmlOrForm(input): XmlOrForm<TextInput>| async { input.name }),
+        )
+        .layer(Extension(config));
+    let harness = TestHarness::new(router).await;
+
+    let data = [
+        (
+            "application/vnd.acme+xml",
+            "<TextInput><name>Jo</name></TextInput>",
+            StatusCode::PAYLOAD_TOO_LARGE,
+        ),
+        (
+            "application/vnd.acme+xml",
+            "<a><name>Jo</name></a>",
+            StatusCode::OK,
+        ),
+        (
+            "application/x-www-form-urlencoded; charset=utf-8",
+            "name=Jo",
+            StatusCode::OK,
+        ),
+        (
+            "application/x-www-form-urlencoded",
+            "name=Jacob&other=value&more=1",
+            StatusCode::PAYLOAD_TOO_LARGE,
+        ),
+    ];
+
+    for (content_type, input, expected) in data {
+        let response = harness
+            .post("/")
+            .header(header::CONTENT_TYPE, content_type)
+            .body(input)
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        assert_eq!(response.status(), expected, "{input}");
+    }
+}
+
+/// Newtype wrapping a collection of inputs
+#[derive(Debug, Deserialize)]
+struct NewtypeItems(Vec<Input>);
+
+/// Structure collecting the children of a wrapping element
+#[derive(Debug, Deserialize)]
+struct WrappedItems {
+    #[serde(rename = "$value")]
+    items: Vec<Input>,
+}
+
+/// Common collection shapes should all deserialize
+#[tokio::test]
+async fn collection_shapes() {
+    fn join(items: Vec<Input>) -> String {
+        items
+            .into_iter()
+            .map(|input| input.foo)
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    let router = Router::new()
+        .route(
+            "/vec",
+            post(|Xml(items): Xml<Vec<Input>>| async move { join(items) }),
+        )
+        .route(
+            "/newtype",
+            post(|Xml(NewtypeItems(items)): Xml<NewtypeItems>| async move { join(items) }),
+        )
+        .route(
+            "/wrapped",
+            post(|Xml(wrapped): Xml<WrappedItems>| async move { join(wrapped.items) }),
+        );
+    let harness = TestHarness::new(router).await;
+
+    let data = [
+        ("/vec", r#"<Input foo="a"/><Input foo="b"/>"#),
+        ("/newtype", r#"<Input foo="a"/><Input foo="b"/>"#),
+        (
+            "/wrapped",
+            r#"<items><Input foo="a"/><Input foo="b"/></items>"#,
+        ),
+    ];
+
+    for (path, input) in data {
+        let response = harness
+            .post(path)
+            .header(header::CONTENT_TYPE, "application/xml")
+            .body(input)
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        let status = response.status();
+        assert_eq!(status, StatusCode::OK, "{path}");
+
+        // Ensure every item was collected
+        let body = response.text().await.expect("Failed to get response text");
+        assert_eq!(body, "a,b", "{path}");
+    }
+}
+
+/// Configs from the request extensions should override the config
+/// provided through the router state
+#[tokio::test]
+async fn config_precedence() {
+    let state_config = XmlConfig::new().entity("source", "state");
+    let extension_config = XmlConfig::new().entity("source", "extension");
+
+    let handler = |Xml(input): Xml<TextInput>| async { input.name };
+    let router = Router::new()
+        .route("/state", post(handler))
+        .route(
+            "/extension",
+            post(handler).layer(Extension(extension_config)),
+        )
+        .with_state(state_config);
+    let harness = TestHarness::new(router).await;
+
+    for (path, expected) in [("/state", "state"), ("/extension", "extension")] {
+        let response = harness
+            .post(path)
+            .header(header::CONTENT_TYPE, "application/xml")
+            .body("<TextInput><name>&source;</name></TextInput>")
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        let status = response.status();
+        assert_eq!(status, StatusCode::OK);
+
+        // Ensure the expected config was used
+        let body = response.text().await.expect("Failed to get response text");
+        assert_eq!(body, expected);
+    }
+}
+
+/// Status where the variant is chosen by the `code` attribute
+#[derive(Debug, Deserialize)]
+#[serde(tag = "@code", rename_all = "lowercase")]
+enum Status {
+    Active,
+    Suspended {
+        #[serde(rename = "@until")]
+        until: String,
+    },
+}
+
+/// Enums should be selected based on the value of an attribute
+#[tokio::test]
+async fn attribute_discriminated_enum() {
+    let router = Router::new().route(
+        "/",
+        post(|Xml(status): Xml<Status>| async move {
+            match status {
+                Status::Active => "active".to_string(),
+                Status::Suspended { until } => format!("suspended {until}"),
+            }
+        }),
+    );
+    let harness = TestHarness::new(router).await;
+
+    let data = [
+        (r#"<status code="active"/>"#, "active"),
+        (
+            r#"<status code="suspended" until="2024-01-01"/>"#,
+            "suspended 2024-01-01",
+        ),
+    ];
+
+    for (input, expected) in data {
+        let response = harness
+            .post("/")
+            .header(header::CONTENT_TYPE, "application/xml")
+            .body(input)
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        let status = response.status();
+        assert_eq!(status, StatusCode::OK);
+
+        // Ensure the correct variant was chosen
+        let body = response.text().await.expect("Failed to get response text");
+        assert_eq!(body, expected);
+    }
+}
+
+/// Rejection responses should carry a marker describing the failure so
+/// middleware can tell XML failures apart from other errors
+#[tokio::test]
+async fn rejection_marker() {
+    use axum::response::IntoResponse;
+
+    let rejection = extract::<TypedInput>("application/xml", "<TypedInput><count>1</count>")
+        .await
+        .expect_err("Extraction should fail");
+
+    let response = rejection.into_response();
+    assert_eq!(
+        response.status(),
+        axum::http::StatusCode::UNPROCESSABLE_ENTITY
+    );
+    assert_eq!(
+        response.extensions().get::<XmlRejected>(),
+        Some(&XmlRejected {
+            kind: XmlErrorKind::Syntax,
+            variant: "InvalidXMLBody",
+        })
+    );
+}
+
+/// Padded attribute values should be trimmed when the option is enabled
+#[tokio::test]
+async fn trim_attribute_values() {
+    async fn test_trim(trim: bool, expected: &str) {
+        let config = XmlConfig::new().trim_attribute_values(trim);
+        let router = Router::new()
+            .route("/", post(|Xml(input): Xml<Input>| async { input.foo }))
+            .layer(Extension(config));
+        let harness = TestHarness::new(router).await;
+
+        let response = harness
+            .post("/")
+            .header(header::CONTENT_TYPE, "application/xml")
+            .body(r#"<Input foo="  bar &amp; baz "/>"#)
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        let status = response.status();
+        assert_eq!(status, StatusCode::OK);
+
+        let body = response.text().await.expect("Failed to get response text");
+        assert_eq!(body, expected);
+    }
+
+    test_trim(true, "bar & baz").await;
+    test_trim(false, "  bar & baz ").await;
+
+    let config = XmlConfig::new().trim_attribute_values(true);
+    Xml::<Input>::from_bytes_with_config(br#"<Input foo="bar" foo="baz"/>"#, &config)
+        .expect_err("Duplicate attributes should be rejected");
+}
+
+/// Documents should deserialize into a self-describing value without a schema
+#[tokio::test]
+async fn deserialize_dynamic_value() {
+    use serde_value::Value;
+
+    let Xml(value) = extract::<Value>(
+        "application/xml",
+        r#"<user id="1"><name>Jacob</name></user>"#,
+    )
+    .await
+    .expect("Failed to extract dynamic value");
+
+    let Value::Map(fields) = value else {
+        panic!("Expected a map value, got {value:?}");
+    };
+
+    let field = |name: &str| fields.get(&Value::String(name.to_string())).cloned();
+    assert_eq!(field("@id"), Some(Value::String("1".to_string())));
+
+    // Element text content is nested under the `$text` key
+    let name = Value::Map(
+        [(
+            Value::String("$text".to_string()),
+            Value::String("Jacob".to_string()),
+        )]
+        .into(),
+    );
+    assert_eq!(field("name"), Some(name));
+}
+
+/// Processing instructions should be skipped unless the strict option is enabled
+#[tokio::test]
+async fn processing_instructions() {
+    async fn test_pi(reject: bool, expected: StatusCode) {
+        let config = XmlConfig::new().reject_processing_instructions(reject);
+        let router = Router::new()
+            .route("/", post(|Xml(input): Xml<Input>| async { input.foo }))
+            .layer(Extension(config));
+        let harness = TestHarness::new(router).await;
+
+        let response = harness
+            .post("/")
+            .header(header::CONTENT_TYPE, "application/xml")
+            .body(
+                r#"<?xml version="1.0"?><?xml-stylesheet type="text/xsl" href="style.xsl"?><Input foo="bar"/>"#,
+            )
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        let status = response.status();
+        assert_eq!(status, expected);
+    }
+
+    test_pi(false, StatusCode::OK).await;
+    test_pi(true, StatusCode::BAD_REQUEST).await;
+}
+
+/// Input structure produced by a buggy producer
+#[derive(Debug, Deserialize)]
+struct Note {
+    title: String,
+    body: String,
+}
+
+/// Malformed documents should only be accepted when repairing
+#[tokio::test]
+async fn repair_malformed() {
+    async fn test_repair(repair: bool, body: &'static str) -> Result<Note, XmlRejection> {
+        let mut request = Request::builder()
+            .header(axum::http::header::CONTENT_TYPE, "application/xml")
+            .body(Body::from(body))
+            .unwrap();
+        request
+            .extensions_mut()
+            .insert(XmlConfig::new().repair_malformed(repair));
+
+        Xml::<Note>::from_request(request, &())
+            .await
+            .map(|Xml(note)| note)
+    }
+
+    let data = [
+        (
+            "<Note><title>Tom & Jerry</title><body>a &amp; b &#38; c</body></Note>",
+            "Tom & Jerry",
+            "a & b & c",
+        ),
+        (
+            r#"<Note><title>Hello</title><br><img src="a.png"><body>Text</body><hr></hr></Note>"#,
+            "Hello",
+            "Text",
+        ),
+        (
+            "<Note><title><![CDATA[A & B]]></title><body>R&D</body><!-- & --></Note>",
+            "A & B",
+            "R&D",
+        ),
+    ];
+
+    for (body, title, text) in data {
+        test_repair(false, body)
+            .await
+            .expect_err("Malformed documents should be rejected");
+
+        let note = test_repair(true, body)
+            .await
+            .unwrap_or_else(|err| panic!("{body:?} should be repaired: {err}"));
+        assert_eq!(note.title, title);
+        assert_eq!(note.body, text);
+    }
+}
+
+/// Byte order marks should be stripped unless rejected
+#[tokio::test]
+async fn reject_byte_order_mark() {
+    async fn test_bom(reject: bool, expected: StatusCode) {
+        let config = XmlConfig::new().reject_byte_order_mark(reject);
+        let router = Router::new()
+            .route("/", post(|Xml(input): Xml<Input>| async { input.foo }))
+            .layer(Extension(config));
+        let harness = TestHarness::new(router).await;
+
+        let response = harness
+            .post("/")
+            .header(header::CONTENT_TYPE, "application/xml")
+            .body("\u{FEFF}<Input foo=\"bar\"/>")
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        let status = response.status();
+        assert_eq!(status, expected);
+
+        let body = response.text().await.expect("Failed to read body");
+        if reject {
+            assert_eq!(body, "Request body must not start with a byte order mark");
+        } else {
+            assert_eq!(body, "bar");
+        }
+    }
+
+    test_bom(false, StatusCode::OK).await;
+    test_bom(true, StatusCode::BAD_REQUEST).await;
+}
+
+/// Syntax errors should report their position in the body
+#[tokio::test]
+async fn syntax_error_position() {
+    let router = Router::new().route("/", post(|Xml(input): Xml<Input>| async { input.foo }));
+    let harness = TestHarness::new(router).await;
+
+    let response = harness
+        .post("/")
+        .header(header::CONTENT_TYPE, "application/xml")
+        .body("<Input foo=\"bar\">\n  <Broken>\n</Input>")
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    let status = response.status();
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let body = response.text().await.expect("Failed to get response text");
+    assert_eq!(
+        body,
+        "Failed to parse the request body as XML at byte 31 (line 3, column 3)"
+    );
+}
+
+/// Syntax errors should include a hexdump of the body around the error
+#[cfg(feature = "hexdump")]
+#[tokio::test]
+async fn hexdump_errors() {
+    let config = XmlConfig::new().hexdump_errors(true);
+    let router = Router::new()
+        .route("/", post(|Xml(input): Xml<Input>| async { input.foo }))
+        .layer(Extension(config));
+    let harness = TestHarness::new(router).await;
+
+    let response = harness
+        .post("/")
+        .header(header::CONTENT_TYPE, "application/xml")
+        .body(r#"<Input foo="bar"><Broken></Input>"#)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    let status = response.status();
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let body = response.text().await.expect("Failed to get response text");
+    assert!(body.contains("at byte 27"), "Missing offset: {body}");
+    assert!(
+        body.contains(
+            "00000010  3e 3c 42 72 6f 6b 65 6e 3e 3c 2f>49 6e 70 75 74  |><Broken></Input|"
+        ),
+        "Missing hexdump: {body}"
+    );
+}
+
+/// Bodies over the configured limit should report the limit and the size
+#[tokio::test]
+async fn max_body_size() {
+    let config = XmlConfig::new().max_body_size(Some(8));
+    let router = Router::new()
+        .route("/", post(|Xml(_): Xml<Input>| async {}))
+        .layer(Extension(config.clone()));
+    let harness = TestHarness::new(router).await;
+
+    let response = harness
+        .post("/")
+        .header(header::CONTENT_TYPE, "application/xml")
+        .body(r#"<Input foo="bar"/>"#)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    let status = response.status();
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+
+    let body = response.text().await.expect("Failed to get response text");
+    assert_eq!(
+        body,
+        "Request body exceeded the limit of 8 bytes (received 18 bytes)"
+    );
+
+    // Without a Content-Length the limit is enforced while reading the body
+    let mut request = Request::builder()
+        .header(axum::http::header::CONTENT_TYPE, "application/xml")
+        .body(Body::from(r#"<Input foo="bar"/>"#))
+        .expect("Failed to create request");
+    request.extensions_mut().insert(config);
+
+    let err = Xml::<Input>::from_request(request, &())
+        .await
+        .expect_err("Body over the limit should be rejected");
+    assert_eq!(
+        err.to_string(),
+        "Request body exceeded the limit of 8 bytes"
+    );
+}
+
+/// Recursive structure for deserializing trees
+#[derive(Debug, Deserialize, PartialEq)]
+struct Node {
+    #[serde(rename = "@name")]
+    name: String,
+    #[serde(default, rename = "Node")]
+    children: Vec<Node>,
+}
+
+/// Recursive types should deserialize nested trees within the depth limit
+#[tokio::test]
+async fn recursive_max_depth() {
+    let Xml(node) = extract::<Node>(
+        "application/xml",
+        r#"<Node name="a"><Node name="b"><Node name="c"/></Node><Node name="d"/></Node>"#,
+    )
+    .await
+    .expect("Failed to extract tree");
+
+    let leaf = |name: &str| Node {
+        name: name.to_string(),
+        children: Vec::new(),
+    };
+    assert_eq!(
+        node,
+        Node {
+            name: "a".to_string(),
+            children: vec![
+                Node {
+                    name: "b".to_string(),
+                    children: vec![leaf("c")],
+                },
+                leaf("d"),
+            ],
+        }
+    );
+
+    async fn extract_depth(max_depth: usize) -> Result<Xml<Node>, XmlRejection> {
+        let mut request = Request::builder()
+            .header(axum::http::header::CONTENT_TYPE, "application/xml")
+            .body(Body::from(
+                r#"<Node name="a"><Node name="b"><Node name="c"/></Node></Node>"#,
+            ))
+            .expect("Failed to create request");
+        request
+            .extensions_mut()
+            .insert(XmlConfig::new().max_depth(Some(max_depth)));
+        Xml::<Node>::from_request(request, &()).await
+    }
+
+    extract_depth(3).await.expect("Tree within the limit");
+
+    let err = extract_depth(2)
+        .await
+        .expect_err("Tree deeper than the limit should be rejected");
+    assert!(matches!(err, XmlRejection::LimitExceeded(XmlLimit::Depth)));
+}
+
+/// Deeply nested documents should be rejected by the default depth limit
+/// before they are deserialized
+#[tokio::test]
+async fn default_max_depth() {
+    let router = Router::new().route("/", post(|Xml(node): Xml<Node>| async { node.name }));
+    let harness = TestHarness::new(router).await;
+
+    let input = format!(
+        "{}{}",
+        r#"<Node name="a">"#.repeat(1000),
+        "</Node>".repeat(1000)
+    );
+    let response = harness
+        .post("/")
+        .header(header::CONTENT_TYPE, "application/xml")
+        .body(input)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    let status = response.status();
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let body = response.text().await.expect("Failed to get response text");
+    assert_eq!(body, "Request body exceeded the nesting depth limit");
+}
+
+/// The `application/*` content type should only be accepted when enabled
+#[tokio::test]
+async fn accept_wildcard_type() {
+    async fn test_wildcard(accept: bool, expected: StatusCode) {
+        let config = XmlConfig::new().accept_wildcard_type(accept);
+        let router = Router::new()
+            .route("/", post(|Xml(input): Xml<Input>| async { input.foo }))
+            .layer(Extension(config));
+        let harness = TestHarness::new(router).await;
+
+        let response = harness
+            .post("/")
+            .header(header::CONTENT_TYPE, "application/*")
+            .body(r#"<Input foo="bar"/>"#)
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        let status = response.status();
+        assert_eq!(status, expected);
+    }
+
+    test_wildcard(true, StatusCode::OK).await;
+    test_wildcard(false, StatusCode::UNSUPPORTED_MEDIA_TYPE).await;
+}
+
+/// Custom content types should replace the default XML content types
+#[tokio::test]
+async fn accepted_content_types() {
+    let config = XmlConfig::new()
+        .accepted_content_types(["application/vnd.myapp+xml", "application/octet-stream"]);
+
+    let data = [
+        ("application/vnd.myapp+xml; version=2", true),
+        ("application/octet-stream", true),
+        ("application/xml", false),
+        ("text/plain", false),
+    ];
+
+    for (content_type, accepted) in data {
+        let mut request = Request::builder()
+            .header(axum::http::header::CONTENT_TYPE, content_type)
+            .body(Body::from(r#"<Input foo="bar"/>"#))
+            .unwrap();
+        request.extensions_mut().insert(config.clone());
+
+        let result = Xml::<Input>::from_request(request, &()).await;
+        if accepted {
+            let Xml(input) = result.unwrap_or_else(|err| panic!("{content_type}: {err}"));
+            assert_eq!(input.foo, "bar");
+        } else {
+            assert!(
+                matches!(result, Err(XmlRejection::MissingXMLContentType)),
+                "{content_type} should be rejected"
+            );
+        }
+    }
+}
+
+/// Only documents with an allowed root element should be accepted
+#[tokio::test]
+async fn allowed_roots() {
+    let config = XmlConfig::new().allowed_roots(["Input", "Other"]);
+    let router = Router::new()
+        .route("/", post(|Xml(input): Xml<Input>| async { input.foo }))
+        .layer(Extension(config));
+    let harness = TestHarness::new(router).await;
+
+    let data = [
+        (r#"<Input foo="bar"/>"#, StatusCode::OK, "bar"),
+        (
+            r#"<Unknown foo="bar"/>"#,
+            StatusCode::BAD_REQUEST,
+            "Root element `Unknown` is not allowed, expected one of: Input, Other",
+        ),
+    ];
+
+    for (body, expected_status, expected_body) in data {
+        let response = harness
+            .post("/")
+            .header(header::CONTENT_TYPE, "application/xml")
+            .body(body)
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        let status = response.status();
+        assert_eq!(status, expected_status);
+
+        let body = response.text().await.expect("Failed to get response text");
+        assert_eq!(body, expected_body);
+    }
+}
+
+/// The content type should be read from the configured header
+#[tokio::test]
+async fn content_type_header() {
+    let config = XmlConfig::new().content_type_header(axum::http::HeaderName::from_static(
+        "x-original-content-type",
+    ));
+    let router = Router::new()
+        .route("/", post(|Xml(input): Xml<Input>| async { input.foo }))
+        .layer(Extension(config));
+    let harness = TestHarness::new(router).await;
+
+    let data = [
+        ("x-original-content-type", StatusCode::OK),
+        ("content-type", StatusCode::UNSUPPORTED_MEDIA_TYPE),
+    ];
+
+    for (name, expected) in data {
+        let response = harness
+            .post("/")
+            .header(name, "application/xml")
+            .body(r#"<Input foo="bar"/>"#)
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        let status = response.status();
+        assert_eq!(status, expected);
+    }
+}
+
+/// Bodies received outside of axum should deserialize the same way
+#[test]
+fn from_bytes() {
+    let Xml(input) =
+        Xml::<Input>::from_bytes(br#"<Input foo="bar"/>"#).expect("Failed to deserialize bytes");
+    assert_eq!(input.foo, "bar");
+
+    let err = Xml::<Input>::from_bytes(b"  ").expect_err("Empty bodies should be rejected");
+    assert!(matches!(err, XmlRejection::NoRootElement));
+
+    let config = XmlConfig::new().allowed_roots(["Other"]);
+    let err = Xml::<Input>::from_bytes_with_config(br#"<Input foo="bar"/>"#, &config)
+        .expect_err("Config should be used");
+    assert!(matches!(err, XmlRejection::RootNotAllowed { .. }));
+}
+
+/// Structure sent through the client helper
+#[cfg(feature = "client")]
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct Order {
+    #[serde(rename = "@id")]
+    id: u32,
+    item: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+}
+
+/// Values serialized with the client helper should extract unchanged
+#[cfg(feature = "client")]
+#[tokio::test]
+async fn client_round_trip() {
+    let order = Order {
+        id: 7,
+        item: vec!["apple".to_string(), "pear & plum".to_string()],
+        note: None,
+    };
+
+    let request = Xml(order)
+        .into_request("/orders".parse().unwrap())
+        .expect("Failed to create request");
+    assert_eq!(request.method(), axum::http::Method::POST);
+
+    let Xml(extracted) = Xml::<Order>::from_request(request, &())
+        .await
+        .expect("Failed to extract order");
+    assert_eq!(
+        extracted,
+        Order {
+            id: 7,
+            item: vec!["apple".to_string(), "pear & plum".to_string()],
+            note: None,
+        }
+    );
+}
+
+/// Raw bodies should be returned unchanged when they are well-formed
+#[tokio::test]
+async fn raw_xml() {
+    let router = Router::new().route("/", post(|RawXml(body): RawXml| async { body }));
+    let harness = TestHarness::new(router).await;
+
+    let data = [
+        (
+            "application/xml",
+            r#"<?xml version="1.0"?><Input foo="bar"><!-- note --></Input>"#,
+            StatusCode::OK,
+        ),
+        (
+            "text/plain",
+            r#"<Input foo="bar"/>"#,
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        ),
+        (
+            "application/xml",
+            "<Input><Broken></Input>",
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ),
+        (
+            "application/xml",
+            "<Input><Open>",
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ),
+        ("application/xml", "   ", StatusCode::BAD_REQUEST),
+    ];
+
+    for (content_type, body, expected) in data {
+        let response = harness
+            .post("/")
+            .header(header::CONTENT_TYPE, content_type)
+            .body(body)
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        let status = response.status();
+        assert_eq!(status, expected, "{body:?}");
+
+        if status == StatusCode::OK {
+            let text = response.text().await.expect("Failed to read body");
+            assert_eq!(text, body);
+        }
+    }
+}
+
+/// Timed extraction should report the time taken to parse the body
+#[tokio::test]
+async fn timed_extraction() {
+    let request = Request::builder()
+        .header(axum::http::header::CONTENT_TYPE, "application/xml")
+        .body(Body::from(r#"<Input foo="bar"/>"#))
+        .expect("Failed to create request");
+
+    let TimedXml {
+        value,
+        parse_duration,
+    } = TimedXml::<Input>::from_request(request, &())
+        .await
+        .expect("Failed to extract timed body");
+
+    assert_eq!(value.foo, "bar");
+    assert!(parse_duration > Duration::ZERO);
+}
+
+/// The charset parameter of the content type should be reported
+#[tokio::test]
+async fn content_type_meta() {
+    let data = [
+        ("application/xml", None),
+        ("application/xml; charset=utf-8", Some("utf-8")),
+        ("text/xml;charset=\"ISO-8859-1\"", Some("iso-8859-1")),
+    ];
+
+    for (content_type, charset) in data {
+        let request = Request::builder()
+            .header(axum::http::header::CONTENT_TYPE, content_type)
+            .body(Body::from(r#"<Input foo="bar"/>"#))
+            .unwrap();
+
+        let XmlWithMeta { value, meta } = XmlWithMeta::<Input>::from_request(request, &())
+            .await
+            .unwrap_or_else(|err| panic!("{content_type}: {err}"));
+        assert_eq!(value.foo, "bar");
+        assert_eq!(
+            meta,
+            XmlMeta {
+                had_charset: charset.is_some(),
+                charset: charset.map(str::to_string),
+            }
+        );
+    }
+}
+
+/// Bodies already read by a middleware should be reused from the extensions
+#[tokio::test]
+async fn buffered_body() {
+    async fn buffer_body(request: Request, next: Next) -> axum::response::Response {
+        let (mut parts, body) = request.into_parts();
+        let bytes = axum::body::to_bytes(body, usize::MAX)
+            .await
+            .expect("Failed to buffer body");
+        parts.extensions.insert(bytes);
+
+        // The original body has been consumed
+        next.run(Request::from_parts(parts, Body::empty())).await
+    }
+
+    let router = Router::new()
+        .route("/", post(|Xml(input): Xml<Input>| async { input.foo }))
+        .layer(middleware::from_fn(buffer_body));
+    let harness = TestHarness::new(router).await;
+
+    let response = harness
+        .post("/")
+        .header(header::CONTENT_TYPE, "application/xml")
+        .body(r#"<Input foo="bar"/>"#)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    let status = response.status();
+    assert_eq!(status, StatusCode::OK);
+
+    let body = response.text().await.expect("Failed to get response text");
+    assert_eq!(body, "bar");
+}
+
+/// Documents declaring a version other than 1.0 should only be rejected when
+/// the option is enabled
+#[tokio::test]
+async fn require_version_1_0() {
+    async fn test_version(require: bool, body: &'static str, expected: StatusCode) {
+        let config = XmlConfig::new().require_version_1_0(require);
+        let router = Router::new()
+            .route("/", post(|Xml(input): Xml<Input>| async { input.foo }))
+            .layer(Extension(config));
+        let harness = TestHarness::new(router).await;
+
+        let response = harness
+            .post("/")
+            .header(header::CONTENT_TYPE, "application/xml")
+            .body(body)
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        let status = response.status();
+        assert_eq!(status, expected);
+
+        if status == StatusCode::BAD_REQUEST {
+            let body = response.text().await.expect("Failed to get response text");
+            assert_eq!(body, "Unsupported XML version `1.1`, expected `1.0`");
+        }
+    }
+
+    let v1_0 = r#"<?xml version="1.0"?><Input foo="bar"/>"#;
+    let v1_1 = r#"<?xml version="1.1"?><Input foo="bar"/>"#;
+
+    test_version(false, v1_1, StatusCode::OK).await;
+    test_version(true, v1_0, StatusCode::OK).await;
+    test_version(true, v1_1, StatusCode::BAD_REQUEST).await;
+}
+
+/// The rejection callback should be invoked with each rejection
+#[tokio::test]
+async fn on_rejection() {
+    use std::sync::{Arc, Mutex};
+
+    let rejections: Arc<Mutex<Vec<&'static str>>> = Arc::default();
+    let recorded = rejections.clone();
+    let config = XmlConfig::new().on_rejection(move |rejection| {
+        recorded.lock().unwrap().push(rejection.variant_name());
+    });
+
+    let data = [
+        ("text/plain", r#"<Input foo="bar"/>"#),
+        ("application/xml", r#"<Input foo="bar">"#),
+        ("application/xml", r#"<Input foo="bar"/>"#),
+    ];
+
+    for (content_type, body) in data {
+        let mut request = Request::builder()
+            .header(axum::http::header::CONTENT_TYPE, content_type)
+            .body(Body::from(body))
+            .expect("Failed to create request");
+        request.extensions_mut().insert(config.clone());
+        let _ = Xml::<Input>::from_request(request, &()).await;
+    }
+
+    assert_eq!(
+        *rejections.lock().unwrap(),
+        ["MissingXMLContentType", "InvalidXMLBody"]
+    );
+}
+
+/// Input structure with attributes containing separated values
+#[derive(Debug, Deserialize)]
+struct SeparatedInput {
+    #[serde(rename = "@coords", with = "axum_xml_up::separated::space")]
+    coords: Vec<f64>,
+    #[serde(
+        rename = "@ids",
+        deserialize_with = "axum_xml_up::separated::deserialize::<_, _, ';'>"
+    )]
+    ids: Vec<u32>,
+}
+
+/// Separated attribute values should deserialize into lists
+#[tokio::test]
+async fn separated_attribute_values() {
+    let Xml(input) = extract::<SeparatedInput>(
+        "application/xml",
+        r#"<SeparatedInput coords="1.5  2 -3e2" ids="1;2;3"/>"#,
+    )
+    .await
+    .expect("Failed to extract separated values");
+
+    assert_eq!(input.coords, [1.5, 2.0, -300.0]);
+    assert_eq!(input.ids, [1, 2, 3]);
+
+    // List items may be separated by any whitespace
+    let Xml(input) = extract::<SeparatedInput>(
+        "application/xml",
+        "<SeparatedInput coords=\"\n  1\t2\r\n  3\n\" ids=\"4\"/>",
+    )
+    .await
+    .expect("Failed to extract whitespace separated values");
+    assert_eq!(input.coords, [1.0, 2.0, 3.0]);
+
+    let err = extract::<SeparatedInput>(
+        "application/xml",
+        r#"<SeparatedInput coords="1 two" ids=""/>"#,
+    )
+    .await
+    .expect_err("Invalid items should be rejected");
+    assert!(matches!(err, XmlRejection::InvalidXMLBody(_)));
+}
+
+/// Structure containing binary values encoded as `xsd:hexBinary`
+#[derive(Debug, Deserialize, Serialize)]
+struct Binary {
+    #[serde(rename = "@checksum", with = "axum_xml_up::hex_binary::upper")]
+    checksum: Vec<u8>,
+    #[serde(with = "axum_xml_up::hex_binary::lower")]
+    data: Vec<u8>,
+}
+
+/// Binary values should round-trip through hex encoded text
+#[tokio::test]
+async fn hex_binary_round_trip() {
+    let router = Router::new().route("/", post(|Xml(input): Xml<Binary>| async { Xml(input) }));
+    let harness = TestHarness::new(router).await;
+
+    let response = harness
+        .post("/")
+        .header(header::CONTENT_TYPE, "application/xml")
+        .body(r#"<Binary checksum="0fa3"><data> 00FF7f </data></Binary>"#)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    let status = response.status();
+    assert_eq!(status, StatusCode::OK);
+
+    let body = response.text().await.expect("Failed to read body");
+    assert_eq!(
+        body,
+        r#"<Binary checksum="0FA3"><data>00ff7f</data></Binary>"#
+    );
+
+    let Xml(input) = extract::<Binary>(
+        "application/xml",
+        r#"<Binary checksum="0FA3"><data>00ff7f</data></Binary>"#,
+    )
+    .await
+    .expect("Failed to extract binary values");
+    assert_eq!(input.checksum, [0x0F, 0xA3]);
+    assert_eq!(input.data, [0x00, 0xFF, 0x7F]);
+
+    let err = extract::<Binary>(
+        "application/xml",
+        r#"<Binary checksum="0g"><data/></Binary>"#,
+    )
+    .await
+    .expect_err("Invalid digits should be rejected");
+    assert!(matches!(err, XmlRejection::InvalidXMLBody(_)));
+}
+
+/// Structure containing a field that is itself a result
+#[derive(Debug, Deserialize, Serialize)]
+struct Job {
+    #[serde(with = "axum_xml_up::result")]
+    outcome: Result<u32, String>,
+}
+
+/// Result fields should round-trip through `<ok>` and `<err>` elements
+#[tokio::test]
+async fn result_fields() {
+    let router = Router::new().route("/", post(|Xml(job): Xml<Job>| async { Xml(job) }));
+    let harness = TestHarness::new(router).await;
+
+    let data = [
+        ("<Job><outcome><ok>42</ok></outcome></Job>", Ok(42)),
+        (
+            "<Job><outcome><err>Timed out</err></outcome></Job>",
+            Err("Timed out".to_string()),
+        ),
+    ];
+
+    for (body, expected) in data {
+        let response = harness
+            .post("/")
+            .header(header::CONTENT_TYPE, "application/xml")
+            .body(body)
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let text = response.text().await.expect("Failed to read body");
+        assert_eq!(text, body);
+
+        let Xml(job) = extract::<Job>("application/xml", body)
+            .await
+            .expect("Failed to extract result field");
+        assert_eq!(job.outcome, expected);
+    }
+}
+
+/// Input structure accepting a value from either an attribute or an element
+#[derive(Debug, Deserialize)]
+struct EitherInput {
+    #[serde(rename = "@foo", alias = "foo")]
+    foo: u32,
+}
+
+/// Aliased fields should deserialize from an attribute or a child element
+#[tokio::test]
+async fn attribute_or_element() {
+    let data = [r#"<x foo="1"/>"#, "<x><foo>1</foo></x>"];
+
+    for body in data {
+        let Xml(input) = extract::<EitherInput>("application/xml", body)
+            .await
+            .unwrap_or_else(|err| panic!("{body:?} should be accepted: {err}"));
+        assert_eq!(input.foo, 1);
+    }
+}
+
+/// Handlers returning a result should render rejections using the crate rules
+#[tokio::test]
+async fn result_response() {
+    async fn handler(Xml(input): Xml<TypedInput>) -> Result<Xml<u32>, XmlRejection> {
+        match input.count {
+            0 => Err(XmlRejection::NoRootElement),
+            count => Ok(Xml(count)),
+        }
+    }
+
+    let router = Router::new().route("/", post(handler));
+    let harness = TestHarness::new(router).await;
+
+    let data = [
+        (1, StatusCode::OK, "<value>1</value>"),
+        (
+            0,
+            StatusCode::BAD_REQUEST,
+            "Expected the request body to contain a root element",
+        ),
+    ];
+
+    for (count, expected_status, expected_body) in data {
+        let response = harness
+            .post("/")
+            .header(header::CONTENT_TYPE, "application/xml")
+            .body(format!("<TypedInput><count>{count}</count></TypedInput>"))
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        let status = response.status();
+        assert_eq!(status, expected_status);
+
+        let body = response.text().await.expect("Failed to get response text");
+        assert_eq!(body, expected_body);
+    }
+}
+
+/// Documents with oversized or too many comments should be rejected
+#[tokio::test]
+async fn comment_limits() {
+    let config = XmlConfig::new().max_comment_len(8).max_comments(2);
+    let router = Router::new()
+        .route("/", post(|Xml(input): Xml<Input>| async { input.foo }))
+        .layer(Extension(config));
+    let harness = TestHarness::new(router).await;
+
+    let data = [
+        (
+            r#"<!-- short --><Input foo="bar"><!-- ok --></Input>"#,
+            StatusCode::OK,
+            "bar",
+        ),
+        (
+            r#"<Input foo="bar"><!-- a much longer comment --></Input>"#,
+            StatusCode::BAD_REQUEST,
+            "Request body exceeded the comment length limit",
+        ),
+        (
+            r#"<!--a--><Input foo="bar"><!--b--><!--c--></Input>"#,
+            StatusCode::BAD_REQUEST,
+            "Request body exceeded the comment count limit",
+        ),
+    ];
+
+    for (body, expected_status, expected_body) in data {
+        let response = harness
+            .post("/")
+            .header(header::CONTENT_TYPE, "application/xml")
+            .body(body)
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        let status = response.status();
+        assert_eq!(status, expected_status);
+
+        let body = response.text().await.expect("Failed to get response text");
+        assert_eq!(body, expected_body);
+    }
+}
+
+/// Documents with more distinct element names than the limit should be rejected
+#[test]
+fn max_unique_element_names() {
+    /// Creates a document with `count` distinct element names including the root
+    fn document(count: usize) -> String {
+        let mut document = String::from(r#"<Input foo="bar">"#);
+        for i in 1..count {
+            document.push_str(&format!("<name{i}/><name{i}/>"));
+        }
+        document.push_str("</Input>");
+        document
+    }
+
+    let config = XmlConfig::new().max_unique_element_names(100);
+
+    let Xml(input) = Xml::<Input>::from_bytes_with_config(document(100).as_bytes(), &config)
+        .expect("Documents at the limit should be accepted");
+    assert_eq!(input.foo, "bar");
+
+    let err = Xml::<Input>::from_bytes_with_config(document(101).as_bytes(), &config)
+        .expect_err("Documents over the limit should be rejected");
+    assert!(matches!(
+        err,
+        XmlRejection::LimitExceeded(XmlLimit::UniqueNames)
+    ));
+    assert_eq!(
+        err.to_string(),
+        "Request body exceeded the unique element name limit"
+    );
+}
+
+/// Bodies declaring a length over the default limit should be rejected
+/// without being read, unless the limit is removed
+#[tokio::test]
+async fn default_max_body_size() {
+    let request = || {
+        Request::builder()
+            .header(axum::http::header::CONTENT_TYPE, "application/xml")
+            .header(axum::http::header::CONTENT_LENGTH, 4 * 1024 * 1024)
+            .body(Body::from(r#"<Input foo="bar"/>"#))
+            .expect("Failed to create request")
+    };
+
+    let err = Xml::<Input>::from_request(request(), &())
+        .await
+        .expect_err("Body over the default limit should be rejected");
+    assert!(matches!(
+        err,
+        XmlRejection::BodyTooLarge {
+            limit: 2097152,
+            size: Some(4194304)
+        }
+    ));
+
+    let mut request = request();
+    request
+        .extensions_mut()
+        .insert(XmlConfig::new().max_body_size(None));
+    let Xml(input) = Xml::<Input>::from_request(request, &())
+        .await
+        .expect("Body without a limit should be extracted");
+    assert_eq!(input.foo, "bar");
+}
+
+/// Windows-1252 bodies should be transcoded using the charset of the content
+/// type or the encoding of the XML declaration
+#[cfg(feature = "encoding")]
+#[tokio::test]
+async fn windows_1252_body() {
+    let router = Router::new().route("/", post(|Xml(input): Xml<Input>| async { input.foo }));
+    let harness = TestHarness::new(router).await;
+
+    // "Café €5" encoded as windows-1252
+    let value: &[u8] = b"Caf\xE9 \x805";
+    let data = [
+        ("application/xml; charset=windows-1252", Vec::new()),
+        (
+            "application/xml",
+            br#"<?xml version="1.0" encoding="windows-1252"?>"#.to_vec(),
+        ),
+    ];
+
+    for (content_type, mut body) in data {
+        body.extend_from_slice(b"<Input foo=\"");
+        body.extend_from_slice(value);
+        body.extend_from_slice(b"\"/>");
+
+        let response = harness
+            .post("/")
+            .header(header::CONTENT_TYPE, content_type)
+            .body(body)
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        let status = response.status();
+        assert_eq!(status, StatusCode::OK);
+
+        let body = response.text().await.expect("Failed to get response text");
+        assert_eq!(body, "Café €5");
+    }
+
+    let Xml(input) = Xml::<Input>::from_bytes(
+        b"<?xml version=\"1.0\" encoding=\"windows-1252\"?><Input foo=\"Caf\xE9\"/>",
+    )
+    .expect("Declared encoding should be used outside of axum");
+    assert_eq!(input.foo, "Café");
+}
+
+/// Extractors reading transcoded bodies should not decode them a second time
+/// using the encoding of the XML declaration
+#[cfg(feature = "encoding")]
+#[tokio::test]
+async fn transcoded_body_readers() {
+    use quick_xml::events::Event;
+
+    fn request(body: &'static [u8]) -> Request {
+        Request::builder()
+            .header(axum::http::header::CONTENT_TYPE, "application/xml")
+            .body(Body::from(body))
+            .expect("Failed to create request")
+    }
+
+    let RawXml(text) = RawXml::from_request(
+        request(b"<?xml version=\"1.0\" encoding=\"windows-1252\"?><name>Caf\xE9</name>"),
+        &(),
+    )
+    .await
+    .expect("Extraction should succeed");
+    assert_eq!(
+        text,
+        r#"<?xml version="1.0" encoding="UTF-8"?><name>Café</name>"#
+    );
+
+    let events = XmlEvents::from_request(
+        request(b"<?xml version='1.0' encoding='latin1'?><name>Caf\xE9</name>"),
+        &(),
+    )
+    .await
+    .expect("Extraction should succeed")
+    .collect::<Result<Vec<_>, _>>()
+    .expect("Events should be valid");
+    let text = events
+        .iter()
+        .find_map(|event| match event {
+            Event::Text(text) => Some(text.unescape().expect("Text should be valid")),
+            _ => None,
+        })
+        .expect("Text event should be present");
+    assert_eq!(text, "Café");
+
+    let PartialXml { value, warning } = PartialXml::<Profile>::from_request(
+        request(
+            b"<?xml version=\"1.0\" encoding=\"windows-1252\"?>\
+              <Profile><name>Caf\xE9</name><age>unknown</age></Profile>",
+        ),
+        &(),
+    )
+    .await
+    .expect("Extraction should recover");
+    assert_eq!(value.name, "Café");
+    assert!(warning.is_some());
+}
+
+/// Bodies with an unknown charset should be rejected
+#[cfg(feature = "encoding")]
+#[test]
+fn unsupported_encoding() {
+    let err =
+        Xml::<Input>::from_bytes(br#"<?xml version="1.0" encoding="made-up"?><Input foo="bar"/>"#)
+            .expect_err("Unknown encodings should be rejected");
+    assert!(matches!(err, XmlRejection::UnsupportedEncoding(ref label) if label == "made-up"));
+    assert_eq!(err.kind(), XmlErrorKind::ContentType);
+}
+
+/// Child element of the document used by the item iterator tests
+#[derive(Debug, Deserialize)]
+struct Item {
+    #[serde(rename = "@id")]
+    id: u32,
+    name: Option<String>,
+}
+
+/// Each child element of the root should be yielded in order
+#[tokio::test]
+async fn items_iterator() {
+    let request = Request::builder()
+        .header(axum::http::header::CONTENT_TYPE, "application/xml")
+        .body(Body::from(
+            r#"<?xml version="1.0"?>
+<items>
+    <item id="1"/>
+    <!-- second item -->
+    <item id="2"><name>Two</name></item>
+    <item id="3"><name><![CDATA[<Three>]]></name></item>
+</items>"#,
+        ))
+        .expect("Failed to create request");
+
+    let mut items = XmlItems::<Item>::from_request(request, &())
+        .await
+        .expect("Failed to extract items");
+
+    let item = items.next().unwrap().expect("Failed to read first item");
+    assert_eq!((item.id, item.name), (1, None));
+    let item = items.next().unwrap().expect("Failed to read second item");
+    assert_eq!((item.id, item.name), (2, Some("Two".to_string())));
+    let item = items.next().unwrap().expect("Failed to read third item");
+    assert_eq!((item.id, item.name), (3, Some("<Three>".to_string())));
+    assert!(items.next().is_none());
+}
+
+/// Iteration should stop after an item fails to deserialize
+#[tokio::test]
+async fn items_iterator_error() {
+    let request = Request::builder()
+        .header(axum::http::header::CONTENT_TYPE, "application/xml")
+        .body(Body::from(
+            r#"<items><item id="1"/><item id="x"/><item id="3"/></items>"#,
+        ))
+        .expect("Failed to create request");
+
+    let items = XmlItems::<Item>::from_request(request, &())
+        .await
+        .expect("Failed to extract items");
+
+    let results: Vec<_> = items.collect();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].as_ref().unwrap().id, 1);
+    assert!(results[1].is_err());
+}
+
+/// Rejections should be rendered in each of the structured formats with the
+/// status of the plain text response
+#[tokio::test]
+async fn rejection_formats() {
+    async fn test_format(
+        rejection: XmlRejection,
+        format: RejectionFormat,
+        expected_status: StatusCode,
+        expected_content_type: Option<&str>,
+        expected_body: &str,
+    ) {
+        let response = rejection.into_response_with(format);
+        assert_eq!(response.status().as_u16(), expected_status.as_u16());
+        assert_eq!(
+            response
+                .headers()
+                .get(axum::http::header::CONTENT_TYPE)
+                .map(|value| value.to_str().unwrap()),
+            expected_content_type
+        );
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("Failed to buffer body");
+        assert_eq!(body, expected_body);
+    }
+
+    test_format(
+        XmlRejection::MissingXMLContentType,
+        RejectionFormat::Text,
+        StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        Some("text/plain; charset=utf-8"),
+        "Expected request with `Content-Type: application/xml`",
+    )
+    .await;
+    test_format(
+        XmlRejection::MissingXMLContentType,
+        RejectionFormat::Xml,
+        StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        Some("application/xml"),
+        "<error><code>MissingXMLContentType</code><message>Expected request with `Content-Type: application/xml`</message></error>",
+    )
+    .await;
+    #[cfg(feature = "json")]
+    test_format(
+        XmlRejection::MissingXMLContentType,
+        RejectionFormat::Json,
+        StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        Some("application/json"),
+        r#"{"code":"MissingXMLContentType","message":"Expected request with `Content-Type: application/xml`"}"#,
+    )
+    .await;
+
+    let syntax_error = || Xml::<Input>::from_bytes(b"<Input>\n<foo></Input>").unwrap_err();
+    test_format(
+        syntax_error(),
+        RejectionFormat::Xml,
+        StatusCode::UNPROCESSABLE_ENTITY,
+        Some("application/xml"),
+        "<error><code>InvalidXMLBodyAt</code><message>Failed to parse the request body as XML at byte 15 (line 2, column 8)</message><offset>15</offset></error>",
+    )
+    .await;
+    #[cfg(feature = "json")]
+    test_format(
+        syntax_error(),
+        RejectionFormat::Json,
+        StatusCode::UNPROCESSABLE_ENTITY,
+        Some("application/json"),
+        r#"{"code":"InvalidXMLBodyAt","message":"Failed to parse the request body as XML at byte 15 (line 2, column 8)","offset":15}"#,
+    )
+    .await;
+}
+
+/// The rejection format should be chosen from the `Accept` header
+#[test]
+fn rejection_format_from_accept() {
+    let mut data = vec![
+        ("", RejectionFormat::Text),
+        ("text/html", RejectionFormat::Text),
+        ("text/html, application/xml", RejectionFormat::Xml),
+        (
+            "application/json;q=0, application/xml",
+            RejectionFormat::Xml,
+        ),
+    ];
+    #[cfg(feature = "json")]
+    data.extend([
+        ("application/json", RejectionFormat::Json),
+        ("application/problem+json", RejectionFormat::Json),
+        ("application/json, application/xml", RejectionFormat::Json),
+    ]);
+    #[cfg(not(feature = "json"))]
+    data.push(("application/json, application/xml", RejectionFormat::Xml));
+
+    for (accept, expected) in data {
+        let mut headers = axum::http::HeaderMap::new();
+        headers.insert(axum::http::header::ACCEPT, accept.parse().unwrap());
+        assert_eq!(RejectionFormat::from_headers(&headers), expected);
+    }
+}
+
+/// Text content with `\r\n` and `\r` line endings
+#[derive(Debug, Deserialize)]
+struct Lines {
+    text: String,
+}
+
+/// Line endings in text content should be normalized unless disabled
+#[test]
+fn normalize_line_endings() {
+    let input = b"<Lines><text>one\r\ntwo\rthree&#13;</text></Lines>";
+
+    let Xml(lines) = Xml::<Lines>::from_bytes(input).expect("Failed to deserialize");
+    assert_eq!(lines.text, "one\ntwo\nthree\r");
+
+    let config = XmlConfig::new().normalize_line_endings(false);
+    let Xml(lines) =
+        Xml::<Lines>::from_bytes_with_config(input, &config).expect("Failed to deserialize");
+    assert_eq!(lines.text, "one\r\ntwo\rthree\r");
+}
+
+/// Documents declaring exponentially expanding entities should be rejected
+/// without expanding them
+#[test]
+fn billion_laughs() {
+    let input = r#"<?xml version="1.0"?>
+<!DOCTYPE lolz [
+ <!ENTITY lol "lol">
+ <!ENTITY lol1 "&lol;&lol;&lol;&lol;&lol;&lol;&lol;&lol;&lol;&lol;">
+ <!ENTITY lol2 "&lol1;&lol1;&lol1;&lol1;&lol1;&lol1;&lol1;&lol1;&lol1;&lol1;">
+ <!ENTITY lol3 "&lol2;&lol2;&lol2;&lol2;&lol2;&lol2;&lol2;&lol2;&lol2;&lol2;">
+ <!ENTITY lol4 "&lol3;&lol3;&lol3;&lol3;&lol3;&lol3;&lol3;&lol3;&lol3;&lol3;">
+ <!ENTITY lol5 "&lol4;&lol4;&lol4;&lol4;&lol4;&lol4;&lol4;&lol4;&lol4;&lol4;">
+ <!ENTITY lol6 "&lol5;&lol5;&lol5;&lol5;&lol5;&lol5;&lol5;&lol5;&lol5;&lol5;">
+ <!ENTITY lol7 "&lol6;&lol6;&lol6;&lol6;&lol6;&lol6;&lol6;&lol6;&lol6;&lol6;">
+ <!ENTITY lol8 "&lol7;&lol7;&lol7;&lol7;&lol7;&lol7;&lol7;&lol7;&lol7;&lol7;">
+ <!ENTITY lol9 "&lol8;&lol8;&lol8;&lol8;&lol8;&lol8;&lol8;&lol8;&lol8;&lol8;">
+]>
+<Lines><text>&lol9;</text></Lines>"#;
+
+    let err = Xml::<Lines>::from_bytes(input.as_bytes())
+        .expect_err("Document entities should never be expanded");
+    assert_eq!(err.kind(), XmlErrorKind::Syntax);
+}
+
+/// Documents referencing configured entities more than the limit should be
+/// rejected before they are expanded
+#[test]
+fn max_entity_expansions() {
+    let config = XmlConfig::new()
+        .entity("company", "Acme Inc.")
+        .max_entity_expansions(2);
+
+    // Predefined entities and character references aren't counted
+    let Xml(lines) = Xml::<Lines>::from_bytes_with_config(
+        b"<Lines><text>&company; &amp; &#38; &company;</text></Lines>",
+        &config,
+    )
+    .expect("Documents at the limit should be accepted");
+    assert_eq!(lines.text, "Acme Inc. & & Acme Inc.");
+
+    let err = Xml::<Lines>::from_bytes_with_config(
+        br#"<Lines owner="&company;"><text>&company;&company;</text></Lines>"#,
+        &config,
+    )
+    .expect_err("Documents over the limit should be rejected");
+    assert!(matches!(
+        err,
+        XmlRejection::LimitExceeded(XmlLimit::EntityExpansions)
+    ));
+    assert_eq!(
+        err.to_string(),
+        "Request body exceeded the entity expansion limit"
+    );
+}
+
+/// Order with a nested required element used by the required path tests
+#[derive(Debug, Deserialize)]
+struct CustomerOrder {
+    #[serde(rename = "@id")]
+    id: u32,
+    customer: Customer,
+}
+
+/// Customer of a [`CustomerOrder`]
+#[derive(Debug, Deserialize)]
+struct Customer {
+    email: String,
+}
+
+/// Documents missing a required path should be rejected naming the path
+#[test]
+fn required_paths() {
+    let config = XmlConfig::new().required_paths(["Order/@id", "Order/customer/email"]);
+
+    let Xml(order) = Xml::<CustomerOrder>::from_bytes_with_config(
+        br#"<Order id="1"><customer><email>jacob@example.com</email></customer></Order>"#,
+        &config,
+    )
+    .expect("Documents with all the paths should be accepted");
+    assert_eq!(order.id, 1);
+    assert_eq!(order.customer.email, "jacob@example.com");
+
+    let data: [(&[u8], &str); 2] = [
+        (
+            br#"<Order><customer><email>jacob@example.com</email></customer></Order>"#,
+            "Order/@id",
+        ),
+        (
+            br#"<Order id="1"><email>jacob@example.com</email><customer/></Order>"#,
+            "Order/customer/email",
+        ),
+    ];
+
+    for (input, expected) in data {
+        let err = Xml::<CustomerOrder>::from_bytes_with_config(input, &config)
+            .expect_err("Documents missing a path should be rejected");
+        assert!(matches!(err, XmlRejection::MissingRequiredPath(ref path) if path == expected));
+        assert_eq!(err.kind(), XmlErrorKind::MissingField);
+        assert_eq!(
+            err.to_string(),
+            format!("Missing required element or attribute `{expected}`")
+        );
+    }
+}
+
+/// Rejection messages should be sent as UTF-8 plain text
+#[tokio::test]
+async fn rejection_content_type() {
+    let router = Router::new().route("/", post(|Xml(_): Xml<Input>| async {}));
+    let harness = TestHarness::new(router).await;
+
+    let data = [
+        (
+            "application/xml",
+            "<Input>",
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ),
+        (
+            "text/plain",
+            r#"<Input foo="bar"/>"#,
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        ),
+    ];
+
+    for (content_type, input, expected_status) in data {
+        let response = harness
+            .post("/")
+            .header(header::CONTENT_TYPE, content_type)
+            .body(input)
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        assert_eq!(response.status(), expected_status);
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/plain; charset=utf-8"
+        );
+    }
+}
+
+/// Empty bodies should be extracted as `None` while invalid bodies are still
+/// rejected
+#[tokio::test]
+async fn optional_body() {
+    async fn extract_optional(
+        content_type: Option<&str>,
+        body: &'static str,
+    ) -> Result<OptionalXml<Input>, XmlRejection> {
+        let mut request = Request::builder();
+        if let Some(content_type) = content_type {
+            request = request.header(axum::http::header::CONTENT_TYPE, content_type);
+        }
+        let request = request
+            .body(Body::from(body))
+            .expect("Failed to create request");
+        OptionalXml::<Input>::from_request(request, &()).await
+    }
+
+    let OptionalXml(input) = extract_optional(None, "")
+        .await
+        .expect("Empty bodies should be accepted without a content type");
+    assert!(input.is_none());
+
+    let OptionalXml(input) = extract_optional(Some("application/xml"), r#"<Input foo="bar"/>"#)
+        .await
+        .expect("Valid bodies should be extracted");
+    assert_eq!(input.map(|input| input.foo).as_deref(), Some("bar"));
+
+    let err = extract_optional(Some("application/xml"), "<Input")
+        .await
+        .expect_err("Invalid bodies should be rejected");
+    assert_eq!(err.kind(), XmlErrorKind::Syntax);
+
+    let err = extract_optional(None, r#"<Input foo="bar"/>"#)
+        .await
+        .expect_err("Bodies without an XML content type should be rejected");
+    assert!(matches!(err, XmlRejection::MissingXMLContentType));
+}
+
+/// Extracting a `Result` should hand malformed bodies to the handler instead
+/// of rejecting the request
+#[tokio::test]
+async fn extract_result() {
+    async fn handler(
+        payload: Result<Xml<Input>, XmlRejection>,
+    ) -> (axum::http::StatusCode, String) {
+        match payload {
+            Ok(Xml(input)) => (axum::http::StatusCode::OK, input.foo),
+            Err(rejection) => (
+                axum::http::StatusCode::UNPROCESSABLE_ENTITY,
+                format!("{:?}", rejection.kind()),
+            ),
+        }
+    }
+
+    let router = Router::new().route("/", post(handler));
+    let harness = TestHarness::new(router).await;
+
+    let response = harness
+        .post("/")
+        .header(header::CONTENT_TYPE, "application/xml")
+        .body(r#"<Input foo="bar">"#)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body = response.text().await.expect("Failed to get response text");
+    assert_eq!(body, "Syntax");
+
+    let response = harness
+        .post("/")
+        .header(header::CONTENT_TYPE, "application/xml")
+        .body(r#"<Input foo="bar"/>"#)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.text().await.expect("Failed to get response text");
+    assert_eq!(body, "bar");
+}
+
+/// The attributes of the root element should be captured alongside the value
+#[tokio::test]
+async fn root_attributes() {
+    let request = Request::builder()
+        .header(axum::http::header::CONTENT_TYPE, "application/xml")
+        .body(Body::from(
+            r#"<Input foo="bar" tenant="acme &amp; co" xmlns:x="urn:x" x:region="eu"/>"#,
+        ))
+        .expect("Failed to create request");
+
+    let XmlWithAttributes { value, attributes } =
+        XmlWithAttributes::<Input>::from_request(request, &())
+            .await
+            .expect("Extraction should succeed");
+
+    assert_eq!(value.foo, "bar");
+    assert_eq!(attributes.len(), 4);
+    assert_eq!(attributes["foo"], "bar");
+    assert_eq!(attributes["tenant"], "acme & co");
+    assert_eq!(attributes["xmlns:x"], "urn:x");
+    assert_eq!(attributes["x:region"], "eu");
+}
+
+/// Root attributes should expand the entities registered in the config
+#[tokio::test]
+async fn root_attributes_entities() {
+    let mut request = Request::builder()
+        .header(axum::http::header::CONTENT_TYPE, "application/xml")
+        .body(Body::from(r#"<Input foo="bar" tenant="&company;"/>"#))
+        .expect("Failed to create request");
+    request
+        .extensions_mut()
+        .insert(XmlConfig::new().entity("company", "Acme"));
+
+    let XmlWithAttributes { value, attributes } =
+        XmlWithAttributes::<Input>::from_request(request, &())
+            .await
+            .expect("Extraction should succeed");
+
+    assert_eq!(value.foo, "bar");
+    assert_eq!(attributes["tenant"], "Acme");
+}
+
+/// Root attributes of transcoded bodies should be read as UTF-8
+#[cfg(feature = "encoding")]
+#[tokio::test]
+async fn root_attributes_windows_1252() {
+    let request = Request::builder()
+        .header(axum::http::header::CONTENT_TYPE, "application/xml")
+        .body(Body::from(
+            &b"<?xml version=\"1.0\" encoding=\"windows-1252\"?><Input foo=\"Caf\xE9\"/>"[..],
+        ))
+        .expect("Failed to create request");
+
+    let XmlWithAttributes { value, attributes } =
+        XmlWithAttributes::<Input>::from_request(request, &())
+            .await
+            .expect("Extraction should succeed");
+
+    assert_eq!(value.foo, "Café");
+    assert_eq!(attributes["foo"], "Café");
+}
+
+/// Account using the `@` prefix for its attribute
+#[derive(Debug, Deserialize)]
+struct PrefixedAccount {
+    #[serde(rename = "@id")]
+    id: u32,
+    name: String,
+}
+
+/// Account using the convention from before `@` prefixed attributes
+#[derive(Debug, Deserialize)]
+struct UnprefixedAccount {
+    id: u32,
+    name: String,
+}
+
+/// Both attribute conventions should deserialize from the same document when
+/// unprefixed attributes are enabled
+#[tokio::test]
+async fn unprefixed_attributes() {
+    async fn extract_account<T>(unprefixed: bool) -> Result<Xml<T>, XmlRejection>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let mut request = Request::builder()
+            .header(axum::http::header::CONTENT_TYPE, "application/xml")
+            .body(Body::from(
+                r#"<Account id="7" xmlns:x="urn:x"><name>Jacob &amp; co</name></Account>"#,
+            ))
+            .expect("Failed to create request");
+        request
+            .extensions_mut()
+            .insert(XmlConfig::new().unprefixed_attributes(unprefixed));
+        Xml::<T>::from_request(request, &()).await
+    }
+
+    let Xml(account) = extract_account::<PrefixedAccount>(true)
+        .await
+        .expect("Prefixed attributes should deserialize");
+    assert_eq!((account.id, account.name.as_str()), (7, "Jacob & co"));
+
+    let Xml(account) = extract_account::<UnprefixedAccount>(true)
+        .await
+        .expect("Unprefixed attributes should deserialize");
+    assert_eq!((account.id, account.name.as_str()), (7, "Jacob & co"));
+
+    // Without the option only the prefixed convention matches attributes
+    let rejection = extract_account::<UnprefixedAccount>(false)
+        .await
+        .expect_err("Unprefixed attributes should not deserialize");
+    assert_eq!(rejection.kind(), XmlErrorKind::MissingField);
+
+    let config = XmlConfig::new().unprefixed_attributes(true);
+    Xml::<UnprefixedAccount>::from_bytes_with_config(
+        br#"<Account id="7" id="8"><name>Jacob</name></Account>"#,
+        &config,
+    )
+    .expect_err("Duplicate attributes should be rejected");
+}
+
+/// Streamed bodies should deserialize the same as buffered bodies and still
+/// respect the body size limit
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn streaming_body() {
+    use axum_xml_up::StreamingXml;
+
+    let config = XmlConfig::new().max_body_size(Some(64));
+    let router = Router::new()
+        .route(
+            "/",
+            post(|StreamingXml(input): StreamingXml<Input>| async { input.foo }),
+        )
+        .layer(Extension(config));
+    let harness = TestHarness::new(router).await;
+
+    let response = harness
+        .post("/")
+        .header(header::CONTENT_TYPE, "application/xml")
+        .body(r#"<Input foo="bar &amp; baz"/>"#)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.text().await.expect("Failed to get response text");
+    assert_eq!(body, "bar & baz");
+
+    let response = harness
+        .post("/")
+        .header(header::CONTENT_TYPE, "application/xml")
+        .body(format!(r#"<Input foo="{}"/>"#, "a".repeat(128)))
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+/// Streamed bodies should be rejected once they are nested deeper than the
+/// depth limit, without counting the markup that doesn't open elements
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn streaming_max_depth() {
+    use axum_xml_up::StreamingXml;
+
+    let router = Router::new()
+        .route(
+            "/",
+            post(|StreamingXml(node): StreamingXml<Node>| async move {
+                node.children.len().to_string()
+            }),
+        )
+        .route(
+            "/shallow",
+            post(|StreamingXml(node): StreamingXml<Node>| async move {
+                node.children.len().to_string()
+            })
+            .layer(Extension(XmlConfig::new().max_depth(Some(2)))),
+        );
+    let harness = TestHarness::new(router).await;
+
+    let input = format!(
+        "{}{}",
+        r#"<Node name="a">"#.repeat(10_000),
+        "</Node>".repeat(10_000)
+    );
+    let response = harness
+        .post("/")
+        .header(header::CONTENT_TYPE, "application/xml")
+        .body(input)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = response.text().await.expect("Failed to get response text");
+    assert_eq!(body, "Request body exceeded the nesting depth limit");
+
+    let input = concat!(
+        r#"<?xml version="1.0"?><!DOCTYPE Node [<!ELEMENT Node ANY>]>"#,
+        r#"<Node name="a>"><!-- <Node> --><Node name='b/'><![CDATA[<c>]]></Node>"#,
+        r#"<?pi d?><Node name="e"/></Node>"#,
+    );
+    let response = harness
+        .post("/shallow")
+        .header(header::CONTENT_TYPE, "application/xml")
+        .body(input)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.text().await.expect("Failed to get response text");
+    assert_eq!(body, "2");
+
+    let response = harness
+        .post("/shallow")
+        .header(header::CONTENT_TYPE, "application/xml")
+        .body(r#"<Node name="a"><Node name="b"><Node name="c"/></Node></Node>"#)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+/// The quick-xml error should be available as the source of invalid body
+/// rejections
+#[tokio::test]
+async fn rejection_source() {
+    use quick_xml::DeError;
+    use std::error::Error;
+
+    for body in [
+        "<TypedInput><count>many</count></TypedInput>",
+        "<TypedInput><count>1</wrong></TypedInput>",
+    ] {
+        let rejection = extract::<TypedInput>("application/xml", body)
+            .await
+            .expect_err("Extraction should fail");
+
+        let source = rejection
+            .source()
+            .and_then(|source| source.downcast_ref::<DeError>())
+            .expect("Source should be the quick-xml error");
+        let error = rejection
+            .as_deserialize_error()
+            .expect("Rejection should have a quick-xml error");
+        assert!(std::ptr::eq(source, error), "{body}: {rejection:?}");
+    }
+
+    let rejection = extract::<TypedInput>("application/json", "<TypedInput/>")
+        .await
+        .expect_err("Extraction should fail");
+    assert!(rejection.as_deserialize_error().is_none());
+}
+
+/// Bodies should only be deserialized when their digest matches the header
+#[tokio::test]
+async fn body_digest() {
+    /// Hex encoded sum of the body bytes standing in for a hash function
+    fn checksum(body: &[u8]) -> String {
+        let sum: u32 = body.iter().map(|&byte| u32::from(byte)).sum();
+        format!("{sum:x}")
+    }
+
+    let body = r#"<Input foo="bar"/>"#;
+    let config = XmlConfig::new().body_digest(
+        axum::http::HeaderName::from_static("x-body-digest"),
+        checksum,
+    );
+    let router = Router::new()
+        .route("/", post(|Xml(input): Xml<Input>| async { input.foo }))
+        .layer(Extension(config));
+    let harness = TestHarness::new(router).await;
+
+    let data = [
+        (Some(checksum(body.as_bytes())), StatusCode::OK, "bar"),
+        (
+            Some("0".to_string()),
+            StatusCode::BAD_REQUEST,
+            "Request body did not match the digest in the `x-body-digest` header",
+        ),
+        (
+            None,
+            StatusCode::BAD_REQUEST,
+            "Request body did not match the digest in the `x-body-digest` header",
+        ),
+    ];
+
+    for (digest, expected_status, expected_body) in data {
+        let mut request = harness
+            .post("/")
+            .header(header::CONTENT_TYPE, "application/xml")
+            .body(body);
+        if let Some(digest) = digest {
+            request = request.header("x-body-digest", digest);
+        }
+
+        let response = request.send().await.expect("Failed to send request");
+        assert_eq!(response.status(), expected_status);
+
+        let text = response.text().await.expect("Failed to get response text");
+        assert_eq!(text, expected_body);
+    }
+}
+
+/// Events should be produced for each part of the document
+#[tokio::test]
+async fn extract_events() {
+    use quick_xml::events::Event;
+
+    let request = Request::builder()
+        .header(axum::http::header::CONTENT_TYPE, "application/xml")
+        .body(Body::from(
+            r#"<?xml version="1.0"?><order><item id="1">A</item><item id="2"/><note>B</note></order>"#,
+        ))
+        .expect("Failed to create request");
+
+    let events = XmlEvents::from_request(request, &())
+        .await
+        .expect("Extraction should succeed")
+        .collect::<Result<Vec<_>, _>>()
+        .expect("Events should be valid");
+
+    let starts = events
+        .iter()
+        .filter(|event| matches!(event, Event::Start(_)))
+        .count();
+    assert_eq!(starts, 3);
+    assert!(matches!(events.first(), Some(Event::Decl(_))));
+    assert!(matches!(events.last(), Some(Event::End(end)) if end.name().as_ref() == b"order"));
+}
+
+/// Document with an optional section that may be invalid
+#[derive(Debug, Deserialize)]
+struct Profile {
+    name: String,
+    age: Option<u32>,
+}
+
+/// A single invalid optional section should be removed and reported instead
+/// of rejecting the whole body
+#[tokio::test]
+async fn partial_body() {
+    async fn extract_profile(body: impl Into<String>) -> Result<PartialXml<Profile>, XmlRejection> {
+        let request = Request::builder()
+            .header(axum::http::header::CONTENT_TYPE, "application/xml")
+            .body(Body::from(body.into()))
+            .expect("Failed to create request");
+        PartialXml::<Profile>::from_request(request, &()).await
+    }
+
+    let PartialXml { value, warning } =
+        extract_profile("<Profile><name>Jacob</name><age>unknown</age></Profile>")
+            .await
+            .expect("Extraction should recover");
+    assert_eq!(value.name, "Jacob");
+    assert_eq!(value.age, None);
+    let warning = warning.expect("Removed section should be reported");
+    assert_eq!(warning.element, "age");
+    assert_eq!(warning.error.kind(), XmlErrorKind::TypeMismatch);
+
+    let PartialXml { value, warning } =
+        extract_profile("<Profile><name>Jacob</name><age>30</age></Profile>")
+            .await
+            .expect("Extraction should succeed");
+    assert_eq!(value.age, Some(30));
+    assert!(warning.is_none());
+
+    // Required sections can't be removed
+    let rejection = extract_profile("<Profile><age>30</age></Profile>")
+        .await
+        .expect_err("Extraction should fail");
+    assert_eq!(rejection.kind(), XmlErrorKind::MissingField);
+
+    // Only the first child elements are removed
+    let notes = "<note/>".repeat(16);
+    let body = format!("<Profile><name>Jacob</name>{notes}<age>unknown</age></Profile>");
+    let rejection = extract_profile(body)
+        .await
+        .expect_err("Extraction should fail");
+    assert_eq!(rejection.kind(), XmlErrorKind::TypeMismatch);
+}
+
+/// Documents with a DOCTYPE declaration should only be rejected when forbidden
+#[tokio::test]
+async fn forbid_doctype() {
+    let data = [
+        (
+            false,
+            r#"<?xml version="1.0"?><!DOCTYPE Input SYSTEM "file:///etc/passwd"><Input foo="bar"/>"#,
+            StatusCode::OK,
+        ),
+        (
+            true,
+            r#"<?xml version="1.0"?><!DOCTYPE Input SYSTEM "file:///etc/passwd"><Input foo="bar"/>"#,
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            true,
+            r#"<?xml version="1.0"?><Input foo="bar"/>"#,
+            StatusCode::OK,
+        ),
+    ];
+
+    for (forbid, body, expected) in data {
+        let config = XmlConfig::new().forbid_doctype(forbid);
+        let router = Router::new()
+            .route("/", post(|Xml(input): Xml<Input>| async { input.foo }))
+            .layer(Extension(config));
+        let harness = TestHarness::new(router).await;
+
+        let response = harness
+            .post("/")
+            .header(header::CONTENT_TYPE, "application/xml")
+            .body(body)
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        assert_eq!(response.status(), expected, "{body}");
+        let text = response.text().await.expect("Failed to get response text");
+        if expected == StatusCode::OK {
+            assert_eq!(text, "bar");
+        } else {
+            assert_eq!(text, "Request body must not contain a DOCTYPE declaration");
+        }
+    }
+}
+
+/// Bodies with only whitespace or a byte order mark should be treated as
+/// empty when enabled
+#[tokio::test]
+async fn blank_body_as_empty() {
+    async fn test_blank(empty: bool, body: &'static str, expected: StatusCode, text: &str) {
+        let config = XmlConfig::new().blank_body_as_empty(empty);
+        let router = Router::new()
+            .route(
+                "/",
+                post(|OptionalXml(input): OptionalXml<Input>| async move {
+                    input.map_or_else(|| "none".to_string(), |input| input.foo)
+                }),
+            )
+            .layer(Extension(config));
+        let harness = TestHarness::new(router).await;
+
+        let response = harness
+            .post("/")
+            .body(body)
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        assert_eq!(response.status(), expected);
+        let body = response.text().await.expect("Failed to get response text");
+        assert_eq!(body, text);
+    }
+
+    test_blank(true, " \r\n\t ", StatusCode::OK, "none").await;
+    test_blank(true, "\u{FEFF}\n", StatusCode::OK, "none").await;
+    test_blank(
+        false,
+        " \r\n\t ",
+        StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        "Expected request with `Content-Type: application/xml`",
+    )
+    .await;
+}
+
+/// Content types listed in the router state should replace the default XML
+/// content types
+#[tokio::test]
+async fn state_content_types() {
+    let types: Vec<mime::Mime> = vec!["application/vnd.acme+xml"
+        .parse()
+        .expect("Failed to parse mime type")];
+    let router = Router::new()
+        .route("/", post(|Xml(input): Xml<Input>| async { input.foo }))
+        .with_state(types);
+    let harness = TestHarness::new(router).await;
+
+    let data = [
+        ("application/vnd.acme+xml", StatusCode::OK),
+        ("application/vnd.acme+xml; charset=utf-8", StatusCode::OK),
+        ("application/xml", StatusCode::UNSUPPORTED_MEDIA_TYPE),
+        ("text/xml", StatusCode::UNSUPPORTED_MEDIA_TYPE),
+    ];
+
+    for (content_type, expected) in data {
+        let response = harness
+            .post("/")
+            .header(header::CONTENT_TYPE, content_type)
+            .body(r#"<Input foo="bar"/>"#)
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        assert_eq!(response.status(), expected, "{content_type}");
+    }
+}
+
+/// Structure using container level defaults
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Preferences {
+    #[serde(rename = "@theme")]
+    theme: String,
+    page_size: u32,
+    tags: Vec<String>,
+}
+
+/// Structures using `#[serde(default)]` at the container level should be
+/// filled with defaults for minimal documents, and for documents without a
+/// root element when enabled
+#[tokio::test]
+async fn container_default() {
+    async fn test_default(default: bool, body: &'static str, expected: StatusCode, text: &str) {
+        let config = XmlConfig::new().missing_root_as_default(default);
+        let router = Router::new()
+            .route(
+                "/",
+                post(|Xml(input): Xml<Preferences>| async move {
+                    format!("{}:{}:{}", input.theme, input.page_size, input.tags.len())
+                }),
+            )
+            .layer(Extension(config));
+        let harness = TestHarness::new(router).await;
+
+        let response = harness
+            .post("/")
+            .header(header::CONTENT_TYPE, "application/xml")
+            .body(body)
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        assert_eq!(response.status(), expected, "{body:?}");
+        let body = response.text().await.expect("Failed to get response text");
+        assert_eq!(body, text);
+    }
+
+    test_default(false, "<Preferences/>", StatusCode::OK, ":0:0").await;
+    test_default(false, "<Preferences></Preferences>", StatusCode::OK, ":0:0").await;
+    test_default(
+        false,
+        r#"<Preferences theme="dark"><page_size>20</page_size></Preferences>"#,
+        StatusCode::OK,
+        "dark:20:0",
+    )
+    .await;
+    test_default(true, "", StatusCode::OK, ":0:0").await;
+    test_default(true, r#"<?xml version="1.0"?>"#, StatusCode::OK, ":0:0").await;
+    test_default(
+        false,
+        "",
+        StatusCode::BAD_REQUEST,
+        "Expected the request body to contain a root element",
+    )
+    .await;
+}